@@ -13,6 +13,7 @@ const (
 	FCPResponseStatusUnclassifiedError = C.F_C_P_RESPONSE_STATUS_UNCLASSIFIED_ERROR
 	FCPResponseStatusCallerError       = C.F_C_P_RESPONSE_STATUS_CALLER_ERROR
 	FCPResponseStatusReceiverError     = C.F_C_P_RESPONSE_STATUS_RECEIVER_ERROR
+	FCPResponseStatusBusy              = C.F_C_P_RESPONSE_STATUS_BUSY
 )
 
 const (
@@ -52,3 +53,8 @@ const (
 	RegisteredUpdateProofStackedDrg32GiBV1  = C.REGISTERED_UPDATE_PROOF_STACKED_DRG32_GI_B_V1
 	RegisteredUpdateProofStackedDrg64GiBV1  = C.REGISTERED_UPDATE_PROOF_STACKED_DRG64_GI_B_V1
 )
+
+const (
+	CommitmentKindSealed   = C.COMMITMENT_KIND_SEALED
+	CommitmentKindUnsealed = C.COMMITMENT_KIND_UNSEALED
+)
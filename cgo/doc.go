@@ -0,0 +1,19 @@
+// Package cgo contains the raw bindings to the functions exported by
+// libfilcrypto.
+//
+// Every wrapper in this package follows the same ownership rules: responses
+// returned by the Rust side are copied into Go allocated values and the
+// matching destructor is called (via `defer resp.destroy()`) before the
+// wrapper returns. Callers therefore never own memory allocated by Rust,
+// except for values created through the `Alloc*`/`New*` helpers, which must be
+// released with their `Destroy` method once they are no longer needed.
+//
+// Every response also carries a duration_ns field with the time the call spent
+// in Rust, excluding the cgo transition, and an operation_id field matching the
+// id in the call's start and end log lines, which wrappers can read before
+// destroying the response.
+//
+// Most consumers should use the higher level API in the
+// github.com/filecoin-project/filecoin-ffi package, which translates between
+// these types and the go-state-types equivalents.
+package cgo
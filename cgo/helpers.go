@@ -9,9 +9,19 @@ package cgo
 import "C"
 import (
 	"errors"
+	"fmt"
 	"unsafe"
 )
 
+// ErrCallerError is wrapped by the errors of calls that Rust rejected because
+// of invalid arguments, such as a closed file descriptor.
+var ErrCallerError = errors.New("invalid argument")
+
+// ErrBusy is wrapped by the errors of calls that Rust rejected because the
+// concurrency limit for their kind of job, set with InitFilecoinFfi, was
+// reached.
+var ErrBusy = errors.New("too many concurrent jobs")
+
 var (
 	emptyUint8              C.uint8_t              = 0
 	emptyUint64             C.uint64_t             = 0
@@ -222,6 +232,13 @@ func CheckErr(resp result) error {
 		return nil
 	}
 
+	if resp.statusCode() == FCPResponseStatusCallerError {
+		return fmt.Errorf("%w: %s", ErrCallerError, string(resp.errorMsg().slice()))
+	}
+	if resp.statusCode() == FCPResponseStatusBusy {
+		return fmt.Errorf("%w: %s", ErrBusy, string(resp.errorMsg().slice()))
+	}
+
 	return errors.New(string(resp.errorMsg().slice()))
 }
 
@@ -245,6 +262,21 @@ func NewPrivateReplicaInfo(pp RegisteredPoStProof, cacheDirPath string, commR By
 	}
 }
 
+func NewInitConfig(numThreads uint64, disableGpu bool, paramCachePath string, parentCachePath string, logLevel string, maxConcurrentPc1 uint64, maxConcurrentPc2 uint64, maxConcurrentC2 uint64, maxConcurrentWindowPoSt uint64, queueWhenBusy bool) InitConfig {
+	return InitConfig{
+		num_threads:                C.uint64_t(numThreads),
+		disable_gpu:                C.bool(disableGpu),
+		param_cache_path:           AllocSliceBoxedUint8([]byte(paramCachePath)),
+		parent_cache_path:          AllocSliceBoxedUint8([]byte(parentCachePath)),
+		log_level:                  AllocSliceBoxedUint8([]byte(logLevel)),
+		max_concurrent_pc1:         C.uint64_t(maxConcurrentPc1),
+		max_concurrent_pc2:         C.uint64_t(maxConcurrentPc2),
+		max_concurrent_c2:          C.uint64_t(maxConcurrentC2),
+		max_concurrent_window_post: C.uint64_t(maxConcurrentWindowPoSt),
+		queue_when_busy:            C.bool(queueWhenBusy),
+	}
+}
+
 func NewPublicReplicaInfo(pp RegisteredPoStProof, commR ByteArray32, sectorId uint64) PublicReplicaInfo {
 	return PublicReplicaInfo{
 		registered_proof: pp,
@@ -8,6 +8,12 @@ package cgo
 */
 import "C"
 
+// SetVerificationCacheSize sets the number of successful seal and PoSt
+// verifications remembered by Rust, 0 to disable and clear the cache.
+func SetVerificationCacheSize(size uint64) {
+	C.set_verification_cache_size(C.uint64_t(size))
+}
+
 func VerifySeal(registeredProof RegisteredSealProof, commR *ByteArray32, commD *ByteArray32, proverId *ByteArray32, ticket *ByteArray32, seed *ByteArray32, sectorId uint64, proof SliceRefUint8) (bool, error) {
 	resp := C.verify_seal(registeredProof, commR, commD, proverId, ticket, seed, C.uint64_t(sectorId), proof)
 	defer resp.destroy()
@@ -19,6 +25,17 @@ func VerifySeal(registeredProof RegisteredSealProof, commR *ByteArray32, commD *
 	return bool(resp.value), nil
 }
 
+func VerifySealWithPieces(registeredProof RegisteredSealProof, commR *ByteArray32, pieces SliceRefPublicPieceInfo, proverId *ByteArray32, ticket *ByteArray32, seed *ByteArray32, sectorId uint64, proof SliceRefUint8) (bool, error) {
+	resp := C.verify_seal_with_pieces(registeredProof, commR, pieces, proverId, ticket, seed, C.uint64_t(sectorId), proof)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return false, err
+	}
+
+	return bool(resp.value), nil
+}
+
 func VerifyAggregateSealProof(registeredProof RegisteredSealProof, registeredAggregation RegisteredAggregationProof, proverId *ByteArray32, proof SliceRefUint8, commitInputs SliceRefAggregationInputs) (bool, error) {
 	resp := C.verify_aggregate_seal_proof(registeredProof, registeredAggregation, proverId, proof, commitInputs)
 	defer resp.destroy()
@@ -62,6 +79,28 @@ func GeneratePieceCommitment(registeredProof RegisteredSealProof, pieceFdRaw int
 	return resp.value.comm_p.copy(), nil
 }
 
+func GeneratePieceCommitmentFromBytes(registeredProof RegisteredSealProof, piece SliceRefUint8) ([]byte, error) {
+	resp := C.generate_piece_commitment_from_bytes(registeredProof, piece)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.comm_p.copy(), nil
+}
+
+func GeneratePieceCommitments(registeredProof RegisteredSealProof, piecePaths SliceRefSliceBoxedUint8, unpaddedPieceSizes SliceRefUint64) ([][]byte, error) {
+	resp := C.generate_piece_commitments(registeredProof, piecePaths, unpaddedPieceSizes)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copyCommPs(), nil
+}
+
 func GenerateDataCommitment(registeredProof RegisteredSealProof, pieces SliceRefPublicPieceInfo) ([]byte, error) {
 	resp := C.generate_data_commitment(registeredProof, pieces)
 	defer resp.destroy()
@@ -73,6 +112,50 @@ func GenerateDataCommitment(registeredProof RegisteredSealProof, pieces SliceRef
 	return resp.value.copy(), nil
 }
 
+func WritePadded(srcFd int32, dstFd int32) (uint64, error) {
+	resp := C.write_padded(C.int32_t(srcFd), C.int32_t(dstFd))
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return 0, err
+	}
+
+	return uint64(resp.value), nil
+}
+
+func WriteUnpadded(srcFd int32, dstFd int32, offset uint64, len uint64) (uint64, error) {
+	resp := C.write_unpadded(C.int32_t(srcFd), C.int32_t(dstFd), C.uint64_t(offset), C.uint64_t(len))
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return 0, err
+	}
+
+	return uint64(resp.value), nil
+}
+
+func CommitmentToCid(kind CommitmentKind, commitment *ByteArray32) ([]byte, error) {
+	resp := C.commitment_to_cid(kind, commitment)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
+
+func CidToCommitment(kind CommitmentKind, cid SliceRefUint8) ([]byte, error) {
+	resp := C.cid_to_commitment(kind, cid)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
+
 func WriteWithAlignment(registeredProof RegisteredSealProof, srcFd int32, srcSize uint64, dstFd int32, existingPieceSizes SliceRefUint64) (uint64, uint64, []byte, error) {
 	resp := C.write_with_alignment(registeredProof, C.int32_t(srcFd), C.uint64_t(srcSize), C.int32_t(dstFd), existingPieceSizes)
 	defer resp.destroy()
@@ -83,6 +166,26 @@ func WriteWithAlignment(registeredProof RegisteredSealProof, srcFd int32, srcSiz
 	return uint64(resp.value.left_alignment_unpadded), uint64(resp.value.total_write_unpadded), resp.value.comm_p.copy(), nil
 }
 
+func AddPiece(registeredProof RegisteredSealProof, srcFd int32, srcSize uint64, stagedSectorFd int32, existingPieceSizes SliceRefUint64) (uint64, uint64, []byte, error) {
+	resp := C.add_piece(registeredProof, C.int32_t(srcFd), C.uint64_t(srcSize), C.int32_t(stagedSectorFd), existingPieceSizes)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return 0, 0, nil, err
+	}
+
+	return uint64(resp.value.offset_padded), uint64(resp.value.padded_piece_size), resp.value.comm_p.copy(), nil
+}
+
+func GetPieceAlignment(registeredProof RegisteredSealProof, existingPieceSizes SliceRefUint64, pieceSize uint64) (uint64, uint64, uint64, error) {
+	resp := C.get_piece_alignment(registeredProof, existingPieceSizes, C.uint64_t(pieceSize))
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return 0, 0, 0, err
+	}
+
+	return uint64(resp.value.left_alignment_unpadded), uint64(resp.value.right_alignment_unpadded), uint64(resp.value.padded_piece_size), nil
+}
+
 func WriteWithoutAlignment(registeredProof RegisteredSealProof, srcFd int32, srcSize uint64, dstFd int32) (uint64, []byte, error) {
 	resp := C.write_without_alignment(registeredProof, C.int32_t(srcFd), C.uint64_t(srcSize), C.int32_t(dstFd))
 	defer resp.destroy()
@@ -148,6 +251,15 @@ func UnsealRange(registeredProof RegisteredSealProof, cacheDirPath SliceRefUint8
 	return nil
 }
 
+func UnsealRangeVerified(registeredProof RegisteredSealProof, cacheDirPath SliceRefUint8, sealedSectorFdRaw int32, unsealOutputFdRaw int32, sectorId uint64, proverId *ByteArray32, ticket *ByteArray32, commD *ByteArray32, unpaddedByteIndex uint64, unpaddedBytesAmount uint64, expectedCommP *ByteArray32) error {
+	resp := C.unseal_range_verified(registeredProof, cacheDirPath, C.int32_t(sealedSectorFdRaw), C.int32_t(unsealOutputFdRaw), C.uint64_t(sectorId), proverId, ticket, commD, C.uint64_t(unpaddedByteIndex), C.uint64_t(unpaddedBytesAmount), expectedCommP)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return err
+	}
+	return nil
+}
+
 func GenerateWinningPoStSectorChallenge(registeredProof RegisteredPoStProof, randomness *ByteArray32, sectorSetLen uint64, proverId *ByteArray32) ([]uint64, error) {
 	resp := C.generate_winning_post_sector_challenge(registeredProof, randomness, C.uint64_t(sectorSetLen), proverId)
 	defer resp.destroy()
@@ -178,6 +290,16 @@ func GenerateWindowPoSt(randomness *ByteArray32, replicas SliceRefPrivateReplica
 	return proofs, []uint64{}, nil
 }
 
+func CheckSectors(randomness *ByteArray32, replicas SliceRefPrivateReplicaInfo, proverId *ByteArray32) ([]SectorCheckGo, error) {
+	resp := C.check_sectors(randomness, replicas, proverId)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
+
 func GetGpuDevices() ([]string, error) {
 	resp := C.get_gpu_devices()
 	defer resp.destroy()
@@ -188,6 +310,16 @@ func GetGpuDevices() ([]string, error) {
 	return resp.value.copyAsStrings(), nil
 }
 
+func GetGpuBackends() ([]string, error) {
+	resp := C.get_gpu_backends()
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copyAsStrings(), nil
+}
+
 func GetSealVersion(registeredProof RegisteredSealProof) (string, error) {
 	resp := C.get_seal_version(registeredProof)
 	defer resp.destroy()
@@ -208,6 +340,167 @@ func GetPoStVersion(registeredProof RegisteredPoStProof) (string, error) {
 	return string(resp.value.copy()), nil
 }
 
+func GetSealParamsCid(registeredProof RegisteredSealProof) (string, error) {
+	resp := C.get_seal_params_cid(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetSealVerifyingKeyCid(registeredProof RegisteredSealProof) (string, error) {
+	resp := C.get_seal_verifying_key_cid(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetSealParamsPath(registeredProof RegisteredSealProof) (string, error) {
+	resp := C.get_seal_params_path(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetSealVerifyingKeyPath(registeredProof RegisteredSealProof) (string, error) {
+	resp := C.get_seal_verifying_key_path(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetSealCircuitIdentifier(registeredProof RegisteredSealProof) (string, error) {
+	resp := C.get_seal_circuit_identifier(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetPoStParamsCid(registeredProof RegisteredPoStProof) (string, error) {
+	resp := C.get_post_params_cid(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetPoStVerifyingKeyCid(registeredProof RegisteredPoStProof) (string, error) {
+	resp := C.get_post_verifying_key_cid(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetPoStParamsPath(registeredProof RegisteredPoStProof) (string, error) {
+	resp := C.get_post_params_path(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetPoStVerifyingKeyPath(registeredProof RegisteredPoStProof) (string, error) {
+	resp := C.get_post_verifying_key_path(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func GetPoStCircuitIdentifier(registeredProof RegisteredPoStProof) (string, error) {
+	resp := C.get_post_circuit_identifier(registeredProof)
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return "", err
+	}
+
+	return string(resp.value.copy()), nil
+}
+
+func RunSelfTest() ([]SelfTestStageGo, error) {
+	resp := C.run_self_test()
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
+
+func GetSupportedSectorSizes() ([]SupportedSectorSizeGo, error) {
+	resp := C.get_supported_sector_sizes()
+	defer resp.destroy()
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
+
+func DeriveChallengeSeed(randomness *ByteArray32) []byte {
+	seed := C.derive_challenge_seed(randomness)
+	return seed.copy()
+}
+
+func PaddedToUnpadded(padded uint64) uint64 {
+	return uint64(C.padded_to_unpadded(C.uint64_t(padded)))
+}
+
+func UnpaddedToPadded(unpadded uint64) uint64 {
+	return uint64(C.unpadded_to_padded(C.uint64_t(unpadded)))
+}
+
+func GetPoStChallengeCount(registeredProof RegisteredPoStProof) uint64 {
+	return uint64(C.get_post_challenge_count(registeredProof))
+}
+
+func GetPoStSectorsPerPartition(registeredProof RegisteredPoStProof) uint64 {
+	return uint64(C.get_post_sectors_per_partition(registeredProof))
+}
+
+func GetPoStChallengesPerPartition(registeredProof RegisteredPoStProof) uint64 {
+	return uint64(C.get_post_challenges_per_partition(registeredProof))
+}
+
+func GetSealPartitions(registeredProof RegisteredSealProof) uint64 {
+	return uint64(C.get_seal_partitions(registeredProof))
+}
+
+func GetSealProofLen(registeredProof RegisteredSealProof) uint64 {
+	return uint64(C.get_seal_proof_len(registeredProof))
+}
+
+func GetPoStProofLen(registeredProof RegisteredPoStProof) uint64 {
+	return uint64(C.get_post_proof_len(registeredProof))
+}
+
+func GetSinglePartitionProofLen() uint64 {
+	return uint64(C.get_single_partition_proof_len())
+}
+
 func GetNumPartitionForFallbackPost(registeredProof RegisteredPoStProof, numSectors uint) (uint, error) {
 	resp := C.get_num_partition_for_fallback_post(registeredProof, C.size_t(numSectors))
 	defer resp.destroy()
@@ -224,6 +517,12 @@ func ClearCache(sectorSize uint64, cacheDirPath SliceRefUint8) error {
 	return CheckErr(resp)
 }
 
+func CreateSectorFile(registeredProof RegisteredSealProof, path SliceRefUint8, preallocate bool) error {
+	resp := C.create_sector_file(registeredProof, path, C.bool(preallocate))
+	defer resp.destroy()
+	return CheckErr(resp)
+}
+
 func Fauxrep(registeredProf RegisteredSealProof, cacheDirPath SliceRefUint8, sealedSectorPath SliceRefUint8) ([]byte, error) {
 	resp := C.fauxrep(registeredProf, cacheDirPath, sealedSectorPath)
 	defer resp.destroy()
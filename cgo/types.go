@@ -20,6 +20,10 @@ type RegisteredUpdateProof = C.RegisteredUpdateProof_t
 
 type FvmRegisteredVersion = C.FvmRegisteredVersion_t
 
+type InitConfig = C.InitConfig_t
+
+type CommitmentKind = C.CommitmentKind_t
+
 type AggregationInputs = C.AggregationInputs_t
 
 type PublicReplicaInfo = C.PublicReplicaInfo_t
@@ -27,6 +31,11 @@ type PrivateReplicaInfo = C.PrivateReplicaInfo_t
 type PartitionSnarkProof = C.PartitionSnarkProof_t
 type PoStProof = C.PoStProof_t
 type PublicPieceInfo = C.PublicPieceInfo_t
+type SelfTestStage = C.SelfTestStage_t
+type SupportedSectorSize = C.SupportedSectorSize_t
+type SectorCheck = C.SectorCheck_t
+type OutstandingResponseCount = C.OutstandingResponseCount_t
+type GeneratePieceCommitment = C.GeneratePieceCommitment_t
 
 type SliceRefPublicReplicaInfo = C.slice_ref_PublicReplicaInfo_t
 type SliceRefPrivateReplicaInfo = C.slice_ref_PrivateReplicaInfo_t
@@ -40,6 +49,11 @@ type SliceRefUint = C.slice_ref_size_t
 type SliceRefAggregationInputs = C.slice_ref_AggregationInputs_t
 
 type SliceBoxedPoStProof = C.struct_slice_boxed_PoStProof
+type SliceBoxedSelfTestStage = C.struct_slice_boxed_SelfTestStage
+type SliceBoxedSupportedSectorSize = C.struct_slice_boxed_SupportedSectorSize
+type SliceBoxedSectorCheck = C.struct_slice_boxed_SectorCheck
+type SliceBoxedOutstandingResponseCount = C.struct_slice_boxed_OutstandingResponseCount
+type SliceBoxedGeneratePieceCommitment = C.struct_slice_boxed_GeneratePieceCommitment
 type SliceBoxedUint64 = C.struct_slice_boxed_uint64
 type SliceBoxedSliceBoxedUint8 = C.slice_boxed_slice_boxed_uint8_t
 type SliceBoxedSliceBoxedUint64 = C.slice_boxed_slice_boxed_uint64_t
@@ -56,6 +70,8 @@ type resultBool = C.Result_bool_t
 type resultGeneratePieceCommitment = C.Result_GeneratePieceCommitment_t
 type resultWriteWithAlignment = C.Result_WriteWithAlignment_t
 type resultWriteWithoutAlignment = C.Result_WriteWithoutAlignment_t
+type resultPieceAlignment = C.Result_PieceAlignment_t
+type resultAddPiece = C.Result_AddPiece_t
 type resultByteArray32 = C.Result_uint8_32_array_t
 type resultVoid = C.Result_void_t
 type resultSealPreCommitPhase2 = C.Result_SealPreCommitPhase2_t
@@ -63,12 +79,18 @@ type resultSliceBoxedUint8 = C.Result_slice_boxed_uint8_t
 type resultSliceBoxedPoStProof = C.Result_slice_boxed_PoStProof_t
 type resultSliceBoxedUint64 = C.Result_slice_boxed_uint64_t
 type resultUint = C.Result_size_t
+type resultUint64 = C.Result_uint64_t
 type resultSliceBoxedSliceBoxedUint8 = C.Result_slice_boxed_slice_boxed_uint8_t
 type resultGenerateWindowPoSt = C.Result_GenerateWindowPoSt_t
 type resultEmptySectorUpdateEncodeInto = C.Result_EmptySectorUpdateEncodeInto_t
 type resultGenerateFallbackSectorChallenges = C.Result_GenerateFallbackSectorChallenges_t
 type resultGenerateSingleWindowPoStWithVanilla = C.Result_GenerateSingleWindowPoStWithVanilla_t
 type resultPoStProof = C.Result_PoStProof_t
+type resultSliceBoxedSelfTestStage = C.Result_slice_boxed_SelfTestStage_t
+type resultSliceBoxedSupportedSectorSize = C.Result_slice_boxed_SupportedSectorSize_t
+type resultSliceBoxedSectorCheck = C.Result_slice_boxed_SectorCheck_t
+type resultSliceBoxedOutstandingResponseCount = C.Result_slice_boxed_OutstandingResponseCount_t
+type resultSliceBoxedGeneratePieceCommitment = C.Result_slice_boxed_GeneratePieceCommitment_t
 
 type resultFvmMachine = C.Result_InnerFvmMachine_ptr_t
 type resultFvmMachineExecuteResponse = C.Result_FvmMachineExecuteResponse_t
@@ -91,6 +113,29 @@ type PoStProofGo struct {
 	Proof           []byte
 }
 
+// SelfTestStageGo is a go allocated version of `SelfTestStage`.
+type SelfTestStageGo struct {
+	Name       string
+	StatusCode FCPResponseStatus
+	ErrorMsg   string
+	DurationMs uint64
+}
+
+// SupportedSectorSizeGo is a go allocated version of `SupportedSectorSize`.
+type SupportedSectorSizeGo struct {
+	SectorSize                 uint64
+	RegisteredSealProof        RegisteredSealProof
+	RegisteredWinningPoStProof RegisteredPoStProof
+	RegisteredWindowPoStProof  RegisteredPoStProof
+}
+
+// SectorCheckGo is a go allocated version of `SectorCheck`.
+type SectorCheckGo struct {
+	SectorID uint64
+	Ok       bool
+	ErrorMsg string
+}
+
 /// FvmMachineExecuteResponse is a go allocated version of `FvmMachineExecuteResponse`.
 type FvmMachineExecuteResponseGo struct {
 	ExitCode             uint64
@@ -399,6 +444,231 @@ func (proof PoStProof) copy() PoStProofGo {
 	}
 }
 
+func (ptr SliceBoxedGeneratePieceCommitment) slice() []GeneratePieceCommitment {
+	if ptr.ptr == nil {
+		return nil
+	}
+	return unsafe.Slice((*GeneratePieceCommitment)(unsafe.Pointer(ptr.ptr)), int(ptr.len))
+}
+
+func (ptr SliceBoxedGeneratePieceCommitment) copyCommPs() [][]byte {
+	ref := ptr.slice()
+	res := make([][]byte, len(ref))
+	for i := range ref {
+		res[i] = ref[i].comm_p.copy()
+	}
+
+	return res
+}
+
+func (ptr *resultPieceAlignment) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultPieceAlignment) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultPieceAlignment) destroy() {
+	if ptr != nil {
+		C.destroy_get_piece_alignment_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr *resultAddPiece) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultAddPiece) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultAddPiece) destroy() {
+	if ptr != nil {
+		C.destroy_add_piece_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr *resultSliceBoxedGeneratePieceCommitment) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultSliceBoxedGeneratePieceCommitment) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultSliceBoxedGeneratePieceCommitment) destroy() {
+	if ptr != nil {
+		C.destroy_generate_piece_commitments_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr SliceBoxedSelfTestStage) slice() []SelfTestStage {
+	if ptr.ptr == nil {
+		return nil
+	}
+	return unsafe.Slice((*SelfTestStage)(unsafe.Pointer(ptr.ptr)), int(ptr.len))
+}
+
+func (ptr SliceBoxedSelfTestStage) copy() []SelfTestStageGo {
+	if ptr.ptr == nil {
+		return nil
+	} else if ptr.len == 0 {
+		return []SelfTestStageGo{}
+	}
+
+	ref := ptr.slice()
+	res := make([]SelfTestStageGo, len(ref))
+	for i := range ref {
+		res[i] = ref[i].copy()
+	}
+
+	return res
+}
+
+func (stage SelfTestStage) copy() SelfTestStageGo {
+	return SelfTestStageGo{
+		Name:       string(stage.name.copy()),
+		StatusCode: FCPResponseStatus(stage.status_code),
+		ErrorMsg:   string(stage.error_msg.copy()),
+		DurationMs: uint64(stage.duration_ms),
+	}
+}
+
+func (ptr *resultSliceBoxedSelfTestStage) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultSliceBoxedSelfTestStage) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultSliceBoxedSelfTestStage) destroy() {
+	if ptr != nil {
+		C.destroy_run_self_test_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr SliceBoxedSupportedSectorSize) slice() []SupportedSectorSize {
+	if ptr.ptr == nil {
+		return nil
+	}
+	return unsafe.Slice((*SupportedSectorSize)(unsafe.Pointer(ptr.ptr)), int(ptr.len))
+}
+
+func (ptr SliceBoxedSupportedSectorSize) copy() []SupportedSectorSizeGo {
+	if ptr.ptr == nil {
+		return nil
+	} else if ptr.len == 0 {
+		return []SupportedSectorSizeGo{}
+	}
+
+	ref := ptr.slice()
+	res := make([]SupportedSectorSizeGo, len(ref))
+	for i := range ref {
+		res[i] = SupportedSectorSizeGo{
+			SectorSize:                 uint64(ref[i].sector_size),
+			RegisteredSealProof:        ref[i].registered_seal_proof,
+			RegisteredWinningPoStProof: ref[i].registered_winning_post_proof,
+			RegisteredWindowPoStProof:  ref[i].registered_window_post_proof,
+		}
+	}
+
+	return res
+}
+
+func (ptr *resultSliceBoxedSupportedSectorSize) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultSliceBoxedSupportedSectorSize) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultSliceBoxedSupportedSectorSize) destroy() {
+	if ptr != nil {
+		C.destroy_get_supported_sector_sizes_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr SliceBoxedSectorCheck) slice() []SectorCheck {
+	if ptr.ptr == nil {
+		return nil
+	}
+	return unsafe.Slice((*SectorCheck)(unsafe.Pointer(ptr.ptr)), int(ptr.len))
+}
+
+func (ptr SliceBoxedSectorCheck) copy() []SectorCheckGo {
+	if ptr.ptr == nil {
+		return nil
+	} else if ptr.len == 0 {
+		return []SectorCheckGo{}
+	}
+
+	ref := ptr.slice()
+	res := make([]SectorCheckGo, len(ref))
+	for i := range ref {
+		res[i] = SectorCheckGo{
+			SectorID: uint64(ref[i].sector_id),
+			Ok:       bool(ref[i].ok),
+			ErrorMsg: string(ref[i].error_msg.copy()),
+		}
+	}
+
+	return res
+}
+
+func (ptr *resultSliceBoxedSectorCheck) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultSliceBoxedSectorCheck) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultSliceBoxedSectorCheck) destroy() {
+	if ptr != nil {
+		C.destroy_check_sectors_response(ptr)
+		ptr = nil
+	}
+}
+
+func (ptr SliceBoxedOutstandingResponseCount) slice() []OutstandingResponseCount {
+	if ptr.ptr == nil {
+		return nil
+	}
+	return unsafe.Slice((*OutstandingResponseCount)(unsafe.Pointer(ptr.ptr)), int(ptr.len))
+}
+
+func (ptr SliceBoxedOutstandingResponseCount) copy() map[string]int64 {
+	res := make(map[string]int64)
+	for _, count := range ptr.slice() {
+		res[string(count.response_type.copy())] = int64(count.outstanding)
+	}
+
+	return res
+}
+
+func (ptr *resultSliceBoxedOutstandingResponseCount) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultSliceBoxedOutstandingResponseCount) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultSliceBoxedOutstandingResponseCount) destroy() {
+	if ptr != nil {
+		C.destroy_outstanding_response_counts_response(ptr)
+		ptr = nil
+	}
+}
+
 func (ptr *resultSliceBoxedPoStProof) statusCode() FCPResponseStatus {
 	return FCPResponseStatus(ptr.status_code)
 }
@@ -500,6 +770,21 @@ func (ptr *resultUint) destroy() {
 	}
 }
 
+func (ptr *resultUint64) statusCode() FCPResponseStatus {
+	return FCPResponseStatus(ptr.status_code)
+}
+
+func (ptr *resultUint64) errorMsg() *SliceBoxedUint8 {
+	return &ptr.error_msg
+}
+
+func (ptr *resultUint64) destroy() {
+	if ptr != nil {
+		C.destroy_write_fr32_response(ptr)
+		ptr = nil
+	}
+}
+
 func (ptr *resultEmptySectorUpdateEncodeInto) statusCode() FCPResponseStatus {
 	return FCPResponseStatus(ptr.status_code)
 }
@@ -597,6 +882,15 @@ func (ptr *SliceBoxedUint8) Destroy() {
 	}
 }
 
+func (ptr *InitConfig) Destroy() {
+	if ptr != nil {
+		ptr.param_cache_path.Destroy()
+		ptr.parent_cache_path.Destroy()
+		ptr.log_level.Destroy()
+		ptr = nil
+	}
+}
+
 func (ptr *PrivateReplicaInfo) Destroy() {
 	if ptr != nil {
 		ptr.cache_dir_path.Destroy()
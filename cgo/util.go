@@ -18,3 +18,66 @@ func InitLogFd(fd int32) error {
 
 	return nil
 }
+
+func InitLogFile(path SliceRefUint8, maxSizeBytes uint64, maxFiles uint64) error {
+	resp := C.init_log_file(path, C.uint64_t(maxSizeBytes), C.uint64_t(maxFiles))
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return err
+	}
+
+	return nil
+}
+
+func InitLog() error {
+	resp := C.init_log()
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return err
+	}
+
+	return nil
+}
+
+func ShutdownLog() error {
+	resp := C.shutdown_log()
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return err
+	}
+
+	return nil
+}
+
+func InitFilecoinFfi(config *InitConfig) error {
+	resp := C.init_filecoin_ffi(config)
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return err
+	}
+
+	return nil
+}
+
+// SetResponseTracking enables or disables counting of the responses that are
+// returned by Rust and not destroyed yet. Enabling resets the counts.
+func SetResponseTracking(enabled bool) {
+	C.set_response_tracking(C.bool(enabled))
+}
+
+// GetOutstandingResponseCounts returns the number of undestroyed responses by
+// Rust response type, for the types with a non-zero count.
+func GetOutstandingResponseCounts() (map[string]int64, error) {
+	resp := C.get_outstanding_response_counts()
+	defer resp.destroy()
+
+	if err := CheckErr(resp); err != nil {
+		return nil, err
+	}
+
+	return resp.value.copy(), nil
+}
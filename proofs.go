@@ -53,6 +53,35 @@ func VerifySeal(info proof5.SealVerifyInfo) (bool, error) {
 	return cgo.VerifySeal(sp, &commR, &commD, &proverID, &randomness, &interactiveRandomness, uint64(info.SectorID.Number), cgo.AsSliceRefUint8(info.Proof))
 }
 
+// VerifySealWithPieces is like VerifySeal, but derives the unsealed CID from
+// the provided pieces instead of using info.UnsealedCID, which is ignored.
+func VerifySealWithPieces(info proof5.SealVerifyInfo, pieces []abi.PieceInfo) (bool, error) {
+	sp, err := toFilRegisteredSealProof(info.SealProof)
+	if err != nil {
+		return false, err
+	}
+
+	commR, err := to32ByteCommR(info.SealedCID)
+	if err != nil {
+		return false, err
+	}
+
+	filPublicPieceInfos, err := toFilPublicPieceInfos(pieces)
+	if err != nil {
+		return false, err
+	}
+
+	proverID, err := toProverID(info.Miner)
+	if err != nil {
+		return false, err
+	}
+
+	randomness := cgo.AsByteArray32(info.Randomness)
+	interactiveRandomness := cgo.AsByteArray32(info.InteractiveRandomness)
+
+	return cgo.VerifySealWithPieces(sp, &commR, cgo.AsSliceRefPublicPieceInfo(filPublicPieceInfos), &proverID, &randomness, &interactiveRandomness, uint64(info.SectorID.Number), cgo.AsSliceRefUint8(info.Proof))
+}
+
 func VerifyAggregateSeals(aggregate proof5.AggregateSealVerifyProofAndInfos) (bool, error) {
 	if len(aggregate.Infos) == 0 {
 		return false, xerrors.New("no seal verify infos")
@@ -101,7 +130,8 @@ func VerifyAggregateSeals(aggregate proof5.AggregateSealVerifyProofAndInfos) (bo
 }
 
 // VerifyWinningPoSt returns true if the Winning PoSt-generation operation from which its
-// inputs were derived was valid, and false if not.
+// inputs were derived was valid, and false if not. The randomness is normalized with
+// DeriveChallengeSeed first.
 func VerifyWinningPoSt(info proof5.WinningPoStVerifyInfo) (bool, error) {
 	filPublicReplicaInfos, err := toFilPublicReplicaInfos(info.ChallengedSectors, "winning")
 	if err != nil {
@@ -128,7 +158,8 @@ func VerifyWinningPoSt(info proof5.WinningPoStVerifyInfo) (bool, error) {
 }
 
 // VerifyWindowPoSt returns true if the Winning PoSt-generation operation from which its
-// inputs were derived was valid, and false if not.
+// inputs were derived was valid, and false if not. The randomness is normalized with
+// DeriveChallengeSeed first.
 func VerifyWindowPoSt(info proof5.WindowPoStVerifyInfo) (bool, error) {
 	filPublicReplicaInfos, err := toFilPublicReplicaInfos(info.ChallengedSectors, "window")
 	if err != nil {
@@ -171,8 +202,9 @@ func GeneratePieceCID(proofType abi.RegisteredSealProof, piecePath string, piece
 	return pcd, pieceFile.Close()
 }
 
-// GenerateDataCommitment produces a commitment for the sector containing the
-// provided pieces.
+// GenerateUnsealedCID produces the unsealed CID (comm_d) for the sector
+// containing the provided pieces. The pieces only need their size and piece
+// CID, so this can be used to predict comm_d before the sector is sealed.
 func GenerateUnsealedCID(proofType abi.RegisteredSealProof, pieces []abi.PieceInfo) (cid.Cid, error) {
 	sp, err := toFilRegisteredSealProof(proofType)
 	if err != nil {
@@ -192,6 +224,59 @@ func GenerateUnsealedCID(proofType abi.RegisteredSealProof, pieces []abi.PieceIn
 	return commcid.DataCommitmentV1ToCID(resp)
 }
 
+// GeneratePieceCIDFromBytes produces a piece CID for the provided data held in
+// memory, after padding it with zeros to the next power of two multiple of 127
+// bytes, the way AddPiece pads it in the sector.
+func GeneratePieceCIDFromBytes(proofType abi.RegisteredSealProof, piece []byte) (cid.Cid, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return cid.Undef, err
+	}
+
+	resp, err := cgo.GeneratePieceCommitmentFromBytes(sp, cgo.AsSliceRefUint8(piece))
+	if err != nil {
+		return cid.Undef, err
+	}
+
+	return commcid.PieceCommitmentV1ToCID(resp)
+}
+
+// GeneratePieceCIDsFromFiles produces the piece CIDs for the pieces stored in
+// the files at the provided paths, computing them concurrently. pieceSizes
+// holds the size of each piece, in the same order as paths.
+func GeneratePieceCIDsFromFiles(proofType abi.RegisteredSealProof, paths []string, pieceSizes []abi.UnpaddedPieceSize) ([]cid.Cid, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return nil, err
+	}
+
+	filPaths := make([]cgo.SliceBoxedUint8, len(paths))
+	for i := range paths {
+		filPaths[i] = cgo.AllocSliceBoxedUint8([]byte(paths[i]))
+	}
+	defer makeCleanerSBU(filPaths, len(filPaths))()
+
+	sizes := make([]uint64, len(pieceSizes))
+	for i := range pieceSizes {
+		sizes[i] = uint64(pieceSizes[i])
+	}
+
+	commPs, err := cgo.GeneratePieceCommitments(sp, cgo.AsSliceRefSliceBoxedUint8(filPaths), cgo.AsSliceRefUint64(sizes))
+	if err != nil {
+		return nil, err
+	}
+
+	cids := make([]cid.Cid, len(commPs))
+	for i := range commPs {
+		cids[i], err = commcid.PieceCommitmentV1ToCID(commPs[i])
+		if err != nil {
+			return nil, err
+		}
+	}
+
+	return cids, nil
+}
+
 // GeneratePieceCIDFromFile produces a piece CID for the provided data stored in a given file.
 func GeneratePieceCIDFromFile(proofType abi.RegisteredSealProof, pieceFile *os.File, pieceSize abi.UnpaddedPieceSize) (cid.Cid, error) {
 	sp, err := toFilRegisteredSealProof(proofType)
@@ -244,6 +329,65 @@ func WriteWithAlignment(
 	return abi.UnpaddedPieceSize(leftAlignmentUnpadded), abi.UnpaddedPieceSize(totalWriteUnpadded), commP, nil
 }
 
+// AddPiece appends the piece in pieceFile to the staged sector holding
+// existingPieceSizes, at the aligned offset following those pieces, and
+// returns the resulting piece info and its padded offset in the sector.
+func AddPiece(
+	proofType abi.RegisteredSealProof,
+	pieceFile *os.File,
+	pieceBytes abi.UnpaddedPieceSize,
+	stagedSectorFile *os.File,
+	existingPieceSizes []abi.UnpaddedPieceSize,
+) (piece abi.PieceInfo, offset abi.PaddedPieceSize, retErr error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return abi.PieceInfo{}, 0, err
+	}
+
+	pieceFd := pieceFile.Fd()
+	defer runtime.KeepAlive(pieceFile)
+
+	stagedSectorFd := stagedSectorFile.Fd()
+	defer runtime.KeepAlive(stagedSectorFile)
+
+	filExistingPieceSizes := toFilExistingPieceSizes(existingPieceSizes)
+
+	offsetPadded, paddedSize, commPRaw, err := cgo.AddPiece(sp, int32(pieceFd), uint64(pieceBytes), int32(stagedSectorFd), cgo.AsSliceRefUint64(filExistingPieceSizes))
+	if err != nil {
+		return abi.PieceInfo{}, 0, err
+	}
+
+	commP, err := commcid.PieceCommitmentV1ToCID(commPRaw)
+	if err != nil {
+		return abi.PieceInfo{}, 0, err
+	}
+
+	return abi.PieceInfo{Size: abi.PaddedPieceSize(paddedSize), PieceCID: commP}, abi.PaddedPieceSize(offsetPadded), nil
+}
+
+// GetPieceAlignment returns the left and right alignment WriteWithAlignment
+// applies when writing a piece of pieceBytes after existingPieceSizes, and the
+// padded size the piece then occupies in the sector.
+func GetPieceAlignment(
+	proofType abi.RegisteredSealProof,
+	existingPieceSizes []abi.UnpaddedPieceSize,
+	pieceBytes abi.UnpaddedPieceSize,
+) (leftAlignment, rightAlignment abi.UnpaddedPieceSize, paddedPieceSize abi.PaddedPieceSize, retErr error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return 0, 0, 0, err
+	}
+
+	filExistingPieceSizes := toFilExistingPieceSizes(existingPieceSizes)
+
+	left, right, padded, err := cgo.GetPieceAlignment(sp, cgo.AsSliceRefUint64(filExistingPieceSizes), uint64(pieceBytes))
+	if err != nil {
+		return 0, 0, 0, err
+	}
+
+	return abi.UnpaddedPieceSize(left), abi.UnpaddedPieceSize(right), abi.PaddedPieceSize(padded), nil
+}
+
 // WriteWithoutAlignment
 func WriteWithoutAlignment(
 	proofType abi.RegisteredSealProof,
@@ -512,6 +656,69 @@ func UnsealRange(
 	)
 }
 
+// UnsealRangeVerified is like UnsealRange, but only writes to unsealOutput
+// once the unsealed bytes match pieceCID. The range must cover exactly one
+// piece; to check a whole sector, pass its unsealed CID as pieceCID.
+func UnsealRangeVerified(
+	proofType abi.RegisteredSealProof,
+	cacheDirPath string,
+	sealedSector *os.File,
+	unsealOutput *os.File,
+	sectorNum abi.SectorNumber,
+	minerID abi.ActorID,
+	ticket abi.SealRandomness,
+	unsealedCID cid.Cid,
+	unpaddedByteIndex uint64,
+	unpaddedBytesAmount uint64,
+	pieceCID cid.Cid,
+) error {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return err
+	}
+
+	proverID, err := toProverID(minerID)
+	if err != nil {
+		return err
+	}
+
+	commD, err := to32ByteCommD(unsealedCID)
+	if err != nil {
+		return err
+	}
+
+	var commP cgo.ByteArray32
+	if pieceCID.Equals(unsealedCID) {
+		commP = commD
+	} else {
+		commP, err = to32ByteCommP(pieceCID)
+		if err != nil {
+			return err
+		}
+	}
+
+	sealedSectorFd := sealedSector.Fd()
+	defer runtime.KeepAlive(sealedSector)
+
+	unsealOutputFd := unsealOutput.Fd()
+	defer runtime.KeepAlive(unsealOutput)
+
+	ticketBytes := cgo.AsByteArray32(ticket)
+	return cgo.UnsealRangeVerified(
+		sp,
+		cgo.AsSliceRefUint8([]byte(cacheDirPath)),
+		int32(sealedSectorFd),
+		int32(unsealOutputFd),
+		uint64(sectorNum),
+		&proverID,
+		&ticketBytes,
+		&commD,
+		unpaddedByteIndex,
+		unpaddedBytesAmount,
+		&commP,
+	)
+}
+
 // GenerateWinningPoStSectorChallenge
 func GenerateWinningPoStSectorChallenge(
 	proofType abi.RegisteredPoStProof,
@@ -596,12 +803,103 @@ func GenerateWindowPoSt(
 	return proofs, nil, nil
 }
 
+// CheckSectors checks that the sealed sectors and caches in privateSectorInfo
+// can still be proven, by generating window PoSt vanilla proofs for the
+// challenges derived from randomness and verifying them against each sector's
+// comm_r. It returns the sectors that failed with the reason for each.
+// Corruption outside of the challenged nodes is not detected, so randomness
+// should change between checks.
+func CheckSectors(
+	minerID abi.ActorID,
+	privateSectorInfo SortedPrivateSectorInfo,
+	randomness abi.PoStRandomness,
+) (map[abi.SectorNumber]error, error) {
+	filReplicas, cleanup, err := toFilPrivateReplicaInfos(privateSectorInfo.Values(), "window")
+	if err != nil {
+		return nil, errors.Wrap(err, "failed to create private replica info array for FFI")
+	}
+	defer cleanup()
+
+	proverID, err := toProverID(minerID)
+	if err != nil {
+		return nil, err
+	}
+
+	randomnessBytes := cgo.AsByteArray32(randomness)
+	checks, err := cgo.CheckSectors(&randomnessBytes, cgo.AsSliceRefPrivateReplicaInfo(filReplicas), &proverID)
+	if err != nil {
+		return nil, err
+	}
+
+	bad := make(map[abi.SectorNumber]error)
+	for _, check := range checks {
+		if !check.Ok {
+			bad[abi.SectorNumber(check.SectorID)] = errors.New(check.ErrorMsg)
+		}
+	}
+
+	return bad, nil
+}
+
+// InitConfig is the process wide configuration applied by InitFilecoinFfi.
+// Zero values keep the library defaults.
+type InitConfig struct {
+	// NumThreads is the size of the global rayon thread pool.
+	NumThreads uint64
+	// DisableGPU disables the GPU for proving and tree building.
+	DisableGPU bool
+	// ParamCachePath is the directory holding the Groth parameters and
+	// verifying keys.
+	ParamCachePath string
+	// ParentCachePath is the directory holding the SDR parent cache.
+	ParentCachePath string
+	// LogLevel is a filter in RUST_LOG syntax, e.g. "info".
+	LogLevel string
+	// MaxConcurrent* limit the number of concurrent calls of each kind, 0 for
+	// no limit. Winning PoSt is never limited.
+	MaxConcurrentPreCommit1 uint64
+	MaxConcurrentPreCommit2 uint64
+	MaxConcurrentCommit2    uint64
+	MaxConcurrentWindowPoSt uint64
+	// QueueWhenBusy makes calls over a limit wait instead of failing with
+	// cgo.ErrBusy.
+	QueueWhenBusy bool
+}
+
+// InitFilecoinFfi applies cfg. It must be called once, before any other
+// function of this package, and before starting goroutines that call into C
+// code reading the environment, since the settings are passed to the library
+// through environment variables. A call that fails changes nothing.
+func InitFilecoinFfi(cfg InitConfig) error {
+	config := cgo.NewInitConfig(
+		cfg.NumThreads,
+		cfg.DisableGPU,
+		cfg.ParamCachePath,
+		cfg.ParentCachePath,
+		cfg.LogLevel,
+		cfg.MaxConcurrentPreCommit1,
+		cfg.MaxConcurrentPreCommit2,
+		cfg.MaxConcurrentCommit2,
+		cfg.MaxConcurrentWindowPoSt,
+		cfg.QueueWhenBusy,
+	)
+	defer config.Destroy()
+
+	return cgo.InitFilecoinFfi(&config)
+}
+
 // GetGPUDevices produces a slice of strings, each representing the name of a
 // detected GPU device.
 func GetGPUDevices() ([]string, error) {
 	return cgo.GetGpuDevices()
 }
 
+// GetGPUBackends returns the GPU backends ("cuda", "opencl") the library was
+// compiled with.
+func GetGPUBackends() ([]string, error) {
+	return cgo.GetGpuBackends()
+}
+
 // GetSealVersion
 func GetSealVersion(proofType abi.RegisteredSealProof) (string, error) {
 	sp, err := toFilRegisteredSealProof(proofType)
@@ -622,6 +920,250 @@ func GetPoStVersion(proofType abi.RegisteredPoStProof) (string, error) {
 	return cgo.GetPoStVersion(pp)
 }
 
+// GetSealParamsCid returns the CID of the Groth parameter file used for the provided
+// proof type.
+func GetSealParamsCid(proofType abi.RegisteredSealProof) (string, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetSealParamsCid(sp)
+}
+
+// GetSealVerifyingKeyCid returns the CID of the verifying key-file used for the provided
+// proof type.
+func GetSealVerifyingKeyCid(proofType abi.RegisteredSealProof) (string, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetSealVerifyingKeyCid(sp)
+}
+
+// GetSealParamsPath returns the path from which the Groth parameter file is loaded used for the provided
+// proof type.
+func GetSealParamsPath(proofType abi.RegisteredSealProof) (string, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetSealParamsPath(sp)
+}
+
+// GetSealVerifyingKeyPath returns the path from which the verifying key-file is loaded used for the provided
+// proof type.
+func GetSealVerifyingKeyPath(proofType abi.RegisteredSealProof) (string, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetSealVerifyingKeyPath(sp)
+}
+
+// GetSealCircuitIdentifier returns the identity of the circuit used for the provided
+// proof type.
+func GetSealCircuitIdentifier(proofType abi.RegisteredSealProof) (string, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetSealCircuitIdentifier(sp)
+}
+
+// GetPoStParamsCid returns the CID of the Groth parameter file used for the provided
+// proof type.
+func GetPoStParamsCid(proofType abi.RegisteredPoStProof) (string, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetPoStParamsCid(pp)
+}
+
+// GetPoStVerifyingKeyCid returns the CID of the verifying key-file used for the provided
+// proof type.
+func GetPoStVerifyingKeyCid(proofType abi.RegisteredPoStProof) (string, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetPoStVerifyingKeyCid(pp)
+}
+
+// GetPoStParamsPath returns the path from which the Groth parameter file is loaded used for the provided
+// proof type.
+func GetPoStParamsPath(proofType abi.RegisteredPoStProof) (string, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetPoStParamsPath(pp)
+}
+
+// GetPoStVerifyingKeyPath returns the path from which the verifying key-file is loaded used for the provided
+// proof type.
+func GetPoStVerifyingKeyPath(proofType abi.RegisteredPoStProof) (string, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetPoStVerifyingKeyPath(pp)
+}
+
+// GetPoStCircuitIdentifier returns the identity of the circuit used for the provided
+// proof type.
+func GetPoStCircuitIdentifier(proofType abi.RegisteredPoStProof) (string, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return "", err
+	}
+
+	return cgo.GetPoStCircuitIdentifier(pp)
+}
+
+// RunSelfTest seals, proves and verifies a 2KiB sector and returns the
+// outcome and duration of every stage. It requires the 2KiB parameters to be
+// present in the parameter cache.
+func RunSelfTest() ([]cgo.SelfTestStageGo, error) {
+	return cgo.RunSelfTest()
+}
+
+// DeriveChallengeSeed returns randomness with its two most significant bits
+// cleared, making it a canonical field element. CheckSectors, VerifyWinningPoSt
+// and VerifyWindowPoSt do this themselves; randomness passed to the other PoSt
+// functions must already be in this form.
+func DeriveChallengeSeed(randomness abi.PoStRandomness) abi.PoStRandomness {
+	r := cgo.AsByteArray32(randomness)
+	return cgo.DeriveChallengeSeed(&r)
+}
+
+// SupportedSectorSize is a sector size this library can seal and prove, with
+// the proof types used for it.
+type SupportedSectorSize struct {
+	SectorSize           abi.SectorSize
+	SealProofType        abi.RegisteredSealProof
+	WinningPoStProofType abi.RegisteredPoStProof
+	WindowPoStProofType  abi.RegisteredPoStProof
+}
+
+// GetSupportedSectorSizes returns the sector sizes supported by this build,
+// ordered from the smallest.
+func GetSupportedSectorSizes() ([]SupportedSectorSize, error) {
+	sizes, err := cgo.GetSupportedSectorSizes()
+	if err != nil {
+		return nil, err
+	}
+
+	out := make([]SupportedSectorSize, len(sizes))
+	for i, size := range sizes {
+		sp, err := fromFilRegisteredSealProof(size.RegisteredSealProof)
+		if err != nil {
+			return nil, err
+		}
+
+		winning, err := fromFilRegisteredPoStProof(size.RegisteredWinningPoStProof)
+		if err != nil {
+			return nil, err
+		}
+
+		window, err := fromFilRegisteredPoStProof(size.RegisteredWindowPoStProof)
+		if err != nil {
+			return nil, err
+		}
+
+		out[i] = SupportedSectorSize{
+			SectorSize:           abi.SectorSize(size.SectorSize),
+			SealProofType:        sp,
+			WinningPoStProofType: winning,
+			WindowPoStProofType:  window,
+		}
+	}
+
+	return out, nil
+}
+
+// GetSealPartitions returns the number of partitions in a seal proof of the
+// provided type. A valid seal proof is exactly GetSealPartitions *
+// GetSinglePartitionProofLen bytes long.
+func GetSealPartitions(proofType abi.RegisteredSealProof) (uint64, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetSealPartitions(sp), nil
+}
+
+// GetPoStChallengeCount returns the number of challenges per sector in a PoSt
+// of the provided type.
+func GetPoStChallengeCount(proofType abi.RegisteredPoStProof) (uint64, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetPoStChallengeCount(pp), nil
+}
+
+// GetPoStSectorsPerPartition returns the number of sectors proven by each
+// partition of a PoSt of the provided type.
+func GetPoStSectorsPerPartition(proofType abi.RegisteredPoStProof) (uint64, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetPoStSectorsPerPartition(pp), nil
+}
+
+// GetPoStChallengesPerPartition returns the number of challenges in each
+// partition of a PoSt of the provided type.
+func GetPoStChallengesPerPartition(proofType abi.RegisteredPoStProof) (uint64, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetPoStChallengesPerPartition(pp), nil
+}
+
+// GetSealProofLen returns the length in bytes of a seal proof of the provided
+// type.
+func GetSealProofLen(proofType abi.RegisteredSealProof) (uint64, error) {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetSealProofLen(sp), nil
+}
+
+// GetPoStProofLen returns the length in bytes of a winning PoSt proof of the
+// provided type, or of each partition of a window PoSt proof.
+func GetPoStProofLen(proofType abi.RegisteredPoStProof) (uint64, error) {
+	pp, err := toFilRegisteredPoStProof(proofType)
+	if err != nil {
+		return 0, err
+	}
+
+	return cgo.GetPoStProofLen(pp), nil
+}
+
+// GetSinglePartitionProofLen returns the length in bytes of the proof for a
+// single seal or window PoSt partition.
+func GetSinglePartitionProofLen() uint64 {
+	return cgo.GetSinglePartitionProofLen()
+}
+
 func GetNumPartitionForFallbackPost(proofType abi.RegisteredPoStProof, numSectors uint) (uint, error) {
 	pp, err := toFilRegisteredPoStProof(proofType)
 	if err != nil {
@@ -636,6 +1178,18 @@ func ClearCache(sectorSize uint64, cacheDirPath string) error {
 	return cgo.ClearCache(sectorSize, cgo.AsSliceRefUint8([]byte(cacheDirPath)))
 }
 
+// CreateSectorFile creates a zero filled staged or unsealed sector file of the
+// sector size of proofType at path, which must not exist yet. With preallocate
+// the disk space is reserved up front instead of leaving a sparse file.
+func CreateSectorFile(proofType abi.RegisteredSealProof, path string, preallocate bool) error {
+	sp, err := toFilRegisteredSealProof(proofType)
+	if err != nil {
+		return err
+	}
+
+	return cgo.CreateSectorFile(sp, cgo.AsSliceRefUint8([]byte(path)), preallocate)
+}
+
 func FauxRep(proofType abi.RegisteredSealProof, cacheDirPath string, sealedSectorPath string) (cid.Cid, error) {
 	sp, err := toFilRegisteredSealProof(proofType)
 	if err != nil {
@@ -891,6 +1445,34 @@ func toFilRegisteredPoStProof(p abi.RegisteredPoStProof) (cgo.RegisteredPoStProo
 	}
 }
 
+func fromFilRegisteredSealProof(p cgo.RegisteredSealProof) (abi.RegisteredSealProof, error) {
+	switch p {
+	case cgo.RegisteredSealProofStackedDrg2KiBV1:
+		return abi.RegisteredSealProof_StackedDrg2KiBV1, nil
+	case cgo.RegisteredSealProofStackedDrg8MiBV1:
+		return abi.RegisteredSealProof_StackedDrg8MiBV1, nil
+	case cgo.RegisteredSealProofStackedDrg512MiBV1:
+		return abi.RegisteredSealProof_StackedDrg512MiBV1, nil
+	case cgo.RegisteredSealProofStackedDrg32GiBV1:
+		return abi.RegisteredSealProof_StackedDrg32GiBV1, nil
+	case cgo.RegisteredSealProofStackedDrg64GiBV1:
+		return abi.RegisteredSealProof_StackedDrg64GiBV1, nil
+
+	case cgo.RegisteredSealProofStackedDrg2KiBV11:
+		return abi.RegisteredSealProof_StackedDrg2KiBV1_1, nil
+	case cgo.RegisteredSealProofStackedDrg8MiBV11:
+		return abi.RegisteredSealProof_StackedDrg8MiBV1_1, nil
+	case cgo.RegisteredSealProofStackedDrg512MiBV11:
+		return abi.RegisteredSealProof_StackedDrg512MiBV1_1, nil
+	case cgo.RegisteredSealProofStackedDrg32GiBV11:
+		return abi.RegisteredSealProof_StackedDrg32GiBV1_1, nil
+	case cgo.RegisteredSealProofStackedDrg64GiBV11:
+		return abi.RegisteredSealProof_StackedDrg64GiBV1_1, nil
+	default:
+		return 0, errors.Errorf("no mapping to abi.RegisteredSealProof value available for: %v", p)
+	}
+}
+
 func toFilRegisteredSealProof(p abi.RegisteredSealProof) (cgo.RegisteredSealProof, error) {
 	switch p {
 	case abi.RegisteredSealProof_StackedDrg2KiBV1:
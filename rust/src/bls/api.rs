@@ -11,6 +11,7 @@ use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use rayon::prelude::*;
 use safer_ffi::prelude::*;
+use zeroize::Zeroize;
 
 pub const SIGNATURE_BYTES: usize = 96;
 pub const PRIVATE_KEY_BYTES: usize = 32;
@@ -37,8 +38,10 @@ fn destroy_box_bls_digest(ptr: repr_c::Box<BLSDigest>) {
     drop(ptr);
 }
 
+/// Zeroizes the private key before freeing it.
 #[ffi_export]
-fn destroy_box_bls_private_key(ptr: repr_c::Box<BLSPrivateKey>) {
+fn destroy_box_bls_private_key(mut ptr: repr_c::Box<BLSPrivateKey>) {
+    ptr[..].zeroize();
     drop(ptr);
 }
 
@@ -56,6 +59,8 @@ fn destroy_box_bls_signature(ptr: repr_c::Box<BLSSignature>) {
 /// # Arguments
 ///
 /// * `message` - reference to a message byte array
+///
+/// Result must be freed using `destroy_box_bls_digest`.
 #[ffi_export]
 pub fn hash(message: c_slice::Ref<u8>) -> repr_c::Box<BLSDigest> {
     // call method
@@ -71,7 +76,7 @@ pub fn hash(message: c_slice::Ref<u8>) -> repr_c::Box<BLSDigest> {
 ///
 /// * `flattened_signatures` - byte array containing signatures
 ///
-/// Returns `None` on error. Result must be freed using `destroy_aggregate_response`.
+/// Returns `None` on error. Result must be freed using `destroy_box_bls_signature`.
 #[ffi_export]
 pub fn aggregate(flattened_signatures: c_slice::Ref<u8>) -> Option<repr_c::Box<BLSSignature>> {
     // prep request
@@ -147,13 +152,18 @@ pub fn verify(
 
 /// Verify that a signature is the aggregated signature of the hashed messages
 ///
+/// Hashing and the pairing checks are run in parallel, so a whole block worth of
+/// messages can be verified in a single call.
+///
 /// # Arguments
 ///
 /// * `signature`             - signature byte array (SIGNATURE_BYTES long)
-/// * `messages`              - array containing the pointers to the messages
-/// * `messages_sizes`        - array containing the lengths of the messages
-/// * `messages_len`          - length of the two messages arrays
+/// * `flattened_messages`    - byte array containing the concatenated messages
+/// * `message_sizes`         - array containing the lengths of the messages
 /// * `flattened_public_keys` - byte array containing public keys
+///
+/// Returns `false` if the message sizes do not add up to the length of
+/// `flattened_messages` or if the number of messages and public keys differ.
 #[ffi_export]
 pub fn hash_verify(
     signature: c_slice::Ref<u8>,
@@ -168,14 +178,26 @@ pub fn hash_verify(
     let mut messages: Vec<&[u8]> = Vec::with_capacity(message_sizes.len());
     let mut offset = 0;
     for chunk_size in message_sizes.iter() {
-        messages.push(&flattened_messages[offset..offset + *chunk_size]);
-        offset += *chunk_size
+        let end = match offset.checked_add(*chunk_size) {
+            Some(end) if end <= flattened_messages.len() => end,
+            _ => return false,
+        };
+        messages.push(&flattened_messages[offset..end]);
+        offset = end;
+    }
+
+    if offset != flattened_messages.len() {
+        return false;
     }
 
     if flattened_public_keys.len() % PUBLIC_KEY_BYTES != 0 {
         return false;
     }
 
+    if messages.len() != flattened_public_keys.len() / PUBLIC_KEY_BYTES {
+        return false;
+    }
+
     let public_keys: Vec<_> = try_ffi!(
         flattened_public_keys
             .par_chunks(PUBLIC_KEY_BYTES)
@@ -195,7 +217,9 @@ pub fn private_key_generate() -> repr_c::Box<BLSPrivateKey> {
         .write_bytes(&mut raw_private_key.as_mut())
         .expect("preallocated");
 
-    repr_c::Box::new(raw_private_key)
+    let res = repr_c::Box::new(raw_private_key);
+    raw_private_key[..].zeroize();
+    res
 }
 
 /// Generate a new private key with seed
@@ -214,7 +238,9 @@ pub fn private_key_generate_with_seed(raw_seed: &[u8; 32]) -> repr_c::Box<BLSPri
         .write_bytes(&mut raw_private_key.as_mut())
         .expect("preallocated");
 
-    repr_c::Box::new(raw_private_key)
+    let res = repr_c::Box::new(raw_private_key);
+    raw_private_key[..].zeroize();
+    res
 }
 
 /// Sign a message with a private key and return the signature
@@ -224,7 +250,8 @@ pub fn private_key_generate_with_seed(raw_seed: &[u8; 32]) -> repr_c::Box<BLSPri
 /// * `raw_private_key` - private key byte array
 /// * `message` - message byte array
 ///
-/// Returns `None` when passed invalid arguments.
+/// Returns `None` when passed invalid arguments. Result must be freed using
+/// `destroy_box_bls_signature`.
 #[ffi_export]
 pub fn private_key_sign(
     raw_private_key: c_slice::Ref<u8>,
@@ -308,6 +335,17 @@ mod tests {
 
         assert!(verified);
 
+        // message sizes which don't match the flattened messages
+        let bad_message_sizes = vec![message.len() + 1];
+        let not_verified = hash_verify(
+            signature[..].into(),
+            flattened_messages[..].into(),
+            bad_message_sizes[..].into(),
+            public_key[..].into(),
+        );
+
+        assert!(!not_verified);
+
         let different_message = b"bye world";
         let different_digest = hash(different_message[..].into());
         let not_verified = verify(
@@ -340,6 +378,14 @@ mod tests {
             ],
             &private_key[..],
         );
+
+        let public_key = private_key_public_key(private_key[..].into()).unwrap();
+        let public_key2 = private_key_public_key(private_key[..].into()).unwrap();
+        assert_eq!(&public_key[..], &public_key2[..]);
+
+        // garbage private key
+        let garbage = vec![0xffu8; PRIVATE_KEY_BYTES];
+        assert!(private_key_public_key(garbage[..].into()).is_none());
     }
 
     #[test]
@@ -25,7 +25,9 @@ use super::blockstore::{CgoBlockstore, FakeBlockstore, OverlayBlockstore};
 use super::externs::CgoExterns;
 use super::types::*;
 use crate::destructor;
-use crate::util::types::{catch_panic_response, catch_panic_response_no_default, Result};
+use crate::util::types::{
+    catch_panic_response, catch_panic_response_no_default, new_tracked, Result,
+};
 
 pub type CgoExecutor = DefaultExecutor<
     DefaultKernel<DefaultCallManager<DefaultMachine<OverlayBlockstore<CgoBlockstore>, CgoExterns>>>,
@@ -121,7 +123,7 @@ fn create_fvm_machine(
             let machine =
                 fvm::machine::DefaultMachine::new(&engine, &machine_context, blockstore, externs)?;
 
-            Ok(Some(repr_c::Box::new(InnerFvmMachine {
+            Ok(Some(new_tracked(InnerFvmMachine {
                 machine: Some(Mutex::new(CgoExecutor::new(machine))),
             })))
         })
@@ -7,6 +7,11 @@ pub mod fvm;
 pub mod proofs;
 pub mod util;
 
+/// The proofs API this library is built against. Rust consumers should use it
+/// through this re-export rather than depending on filecoin-proofs-api
+/// directly, so that parameters and configuration always match the C ABI.
+pub use filecoin_proofs_api;
+
 // Generates the headers.
 // Run `HEADER_DIR=<dir> cargo test build_headers --features c-headers` to build
 #[safer_ffi::cfg_headers]
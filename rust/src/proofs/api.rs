@@ -1,19 +1,29 @@
 use std::fs;
 
+use anyhow::{anyhow, ensure, Context};
 use blstrs::Scalar as Fr;
+use cid::Cid;
 use filecoin_proofs_api::seal;
 use filecoin_proofs_api::{
-    self as api, update, PieceInfo, SectorId, StorageProofsError, UnpaddedByteIndex,
-    UnpaddedBytesAmount,
+    self as api, update, PaddedBytesAmount, PieceInfo, SectorId, StorageProofsError,
+    UnpaddedByteIndex, UnpaddedBytesAmount,
 };
+use fvm_shared::commcid;
 use rayon::prelude::*;
 use safer_ffi::prelude::*;
 
-use super::helpers::{to_private_replica_info_map, to_public_replica_info_map};
+use super::helpers::{
+    self, ensure_post_proof_len, ensure_seal_proof_len, ensure_valid_fd, into_safe_challenge_seed,
+    sum_piece_bytes_with_alignment, to_private_replica_info_map, to_public_replica_info_map,
+    SINGLE_PARTITION_PROOF_LEN,
+};
 use super::types::*;
+use super::verification_cache;
 use crate::destructor;
+use crate::util::limiter::{self, Job};
 use crate::util::types::{
-    as_path_buf, catch_panic_response, catch_panic_response_raw, FCPResponseStatus,
+    as_path_buf, catch_panic_response, catch_panic_response_raw, error_message, error_status,
+    new_response, write_response_into, CallerError, FCPResponseStatus,
 };
 
 #[ffi_export]
@@ -26,6 +36,16 @@ fn destroy_boxed_slice(ptr: c_slice::Box<u8>) {
     drop(ptr);
 }
 
+/// Frees an `error_msg` taken out of a response, so that the message can
+/// outlive the response. Before the response is destroyed its `error_msg` must
+/// be replaced with an empty slice, e.g. from `alloc_boxed_slice(0)`, or the
+/// message is freed twice. The slice is never null, so a nulled out pointer
+/// is not a valid replacement. An alias of `destroy_boxed_slice`.
+#[ffi_export]
+fn destroy_string(ptr: c_slice::Box<u8>) {
+    destroy_boxed_slice(ptr)
+}
+
 // A byte serialized representation of a vanilla proof.
 pub type ApiVanillaProof = Vec<u8>;
 
@@ -39,6 +59,9 @@ unsafe fn write_with_alignment(
     existing_piece_sizes: c_slice::Ref<u64>,
 ) -> repr_c::Box<WriteWithAlignmentResponse> {
     catch_panic_response("write_with_alignment", || {
+        ensure_valid_fd(src_fd, "src_fd")?;
+        ensure_valid_fd(dst_fd, "dst_fd")?;
+
         let piece_sizes: Vec<UnpaddedBytesAmount> = existing_piece_sizes
             .iter()
             .copied()
@@ -72,6 +95,9 @@ unsafe fn write_without_alignment(
     dst_fd: libc::c_int,
 ) -> repr_c::Box<WriteWithoutAlignmentResponse> {
     catch_panic_response("write_without_alignment", || {
+        ensure_valid_fd(src_fd, "src_fd")?;
+        ensure_valid_fd(dst_fd, "dst_fd")?;
+
         let (info, written) = seal::write_and_preprocess(
             registered_proof.into(),
             FileDescriptorRef::new(src_fd),
@@ -86,6 +112,96 @@ unsafe fn write_without_alignment(
     })
 }
 
+/// Appends the piece read from `src_fd` to the staged sector file
+/// `staged_sector_fd`, which already holds `existing_piece_sizes` (unpadded).
+/// The piece is written at the end of the existing pieces, after any alignment
+/// padding, regardless of the current position of the file descriptor.
+/// Returns the piece commitment alongside its padded offset and size within
+/// the sector, as needed for its `PublicPieceInfo` and deal proposal.
+#[ffi_export]
+unsafe fn add_piece(
+    registered_proof: RegisteredSealProof,
+    src_fd: libc::c_int,
+    src_size: u64,
+    staged_sector_fd: libc::c_int,
+    existing_piece_sizes: c_slice::Ref<u64>,
+) -> repr_c::Box<AddPieceResponse> {
+    catch_panic_response("add_piece", || {
+        use std::io::{Seek, SeekFrom};
+
+        ensure_valid_fd(src_fd, "src_fd")?;
+        ensure_valid_fd(staged_sector_fd, "staged_sector_fd")?;
+
+        let piece_sizes: Vec<UnpaddedBytesAmount> = existing_piece_sizes
+            .iter()
+            .copied()
+            .map(UnpaddedBytesAmount)
+            .collect();
+        let existing = sum_piece_bytes_with_alignment(&existing_piece_sizes);
+
+        let mut staged_sector = FileDescriptorRef::new(staged_sector_fd);
+        staged_sector.seek(SeekFrom::Start(unpadded_to_padded(existing)))?;
+
+        let n = UnpaddedBytesAmount(src_size);
+        let (info, written) = seal::add_piece(
+            registered_proof.into(),
+            FileDescriptorRef::new(src_fd),
+            staged_sector,
+            n,
+            &piece_sizes,
+        )?;
+        // valid piece sizes are never padded on the right
+        let left_alignment = u64::from(written - n);
+
+        Ok(AddPiece {
+            comm_p: info.commitment,
+            offset_padded: unpadded_to_padded(existing + left_alignment),
+            padded_piece_size: unpadded_to_padded(src_size),
+        })
+    })
+}
+
+/// Reads unpadded bytes from `src_fd` until EOF and writes their fr32 padded
+/// representation to `dst_fd`. Returns the number of padded bytes written.
+#[ffi_export]
+unsafe fn write_padded(src_fd: libc::c_int, dst_fd: libc::c_int) -> repr_c::Box<WriteFr32Response> {
+    catch_panic_response("write_padded", || {
+        ensure_valid_fd(src_fd, "src_fd")?;
+        ensure_valid_fd(dst_fd, "dst_fd")?;
+
+        let mut reader = fr32::Fr32Reader::new(FileDescriptorRef::new(src_fd));
+        let mut writer = FileDescriptorRef::new(dst_fd);
+
+        let written = std::io::copy(&mut reader, &mut writer)?;
+
+        Ok(written)
+    })
+}
+
+/// Removes the fr32 padding from the bytes in `src_fd`, writing `len` unpadded
+/// bytes starting at the unpadded `offset` to `dst_fd`. Returns the number of
+/// unpadded bytes written.
+#[ffi_export]
+unsafe fn write_unpadded(
+    src_fd: libc::c_int,
+    dst_fd: libc::c_int,
+    offset: u64,
+    len: u64,
+) -> repr_c::Box<WriteFr32Response> {
+    catch_panic_response("write_unpadded", || {
+        ensure_valid_fd(src_fd, "src_fd")?;
+        ensure_valid_fd(dst_fd, "dst_fd")?;
+
+        let src = FileDescriptorRef::new(src_fd);
+        let padded = memmap::MmapOptions::new().map(src.as_file())?;
+        let mut writer = FileDescriptorRef::new(dst_fd);
+
+        let written = fr32::write_unpadded(&padded, &mut writer, offset as usize, len as usize)?;
+
+        Ok(written as u64)
+    })
+}
+
 #[ffi_export]
 fn fauxrep(
     registered_proof: RegisteredSealProof,
@@ -132,6 +248,8 @@ fn seal_pre_commit_phase1(
     pieces: c_slice::Ref<PublicPieceInfo>,
 ) -> repr_c::Box<SealPreCommitPhase1Response> {
     catch_panic_response("seal_pre_commit_phase1", || {
+        let _job = limiter::acquire(Job::PreCommit1)?;
+
         let public_pieces: Vec<PieceInfo> = pieces.iter().map(Into::into).collect();
 
         let result = seal::seal_pre_commit_phase1(
@@ -158,6 +276,8 @@ fn seal_pre_commit_phase2(
     sealed_sector_path: c_slice::Ref<u8>,
 ) -> repr_c::Box<SealPreCommitPhase2Response> {
     catch_panic_response("seal_pre_commit_phase2", || {
+        let _job = limiter::acquire(Job::PreCommit2)?;
+
         let phase_1_output = serde_json::from_slice(&seal_pre_commit_phase1_output)?;
 
         let output = seal::seal_pre_commit_phase2(
@@ -220,6 +340,8 @@ fn seal_commit_phase2(
     prover_id: &[u8; 32],
 ) -> repr_c::Box<SealCommitPhase2Response> {
     catch_panic_response("seal_commit_phase2", || {
+        let _job = limiter::acquire(Job::Commit2)?;
+
         let scp1o = serde_json::from_slice(&seal_commit_phase1_output)?;
         let result = seal::seal_commit_phase2(scp1o, *prover_id, SectorId::from(sector_id))?;
 
@@ -306,7 +428,34 @@ fn verify_aggregate_seal_proof(
     })
 }
 
-/// TODO: document
+/// Like `verify_aggregate_seal_proof`, but reports through caller provided
+/// memory, see `verify_seal_with_err_buf`.
+#[ffi_export]
+fn verify_aggregate_seal_proof_with_err_buf(
+    registered_proof: RegisteredSealProof,
+    registered_aggregation: RegisteredAggregationProof,
+    prover_id: &[u8; 32],
+    proof: c_slice::Ref<u8>,
+    commit_inputs: c_slice::Ref<AggregationInputs>,
+    err_buf: c_slice::Mut<u8>,
+    valid: &mut bool,
+) -> FCPResponseStatus {
+    let response = verify_aggregate_seal_proof(
+        registered_proof,
+        registered_aggregation,
+        prover_id,
+        proof,
+        commit_inputs,
+    );
+
+    write_response_into(response, err_buf, valid)
+}
+
+/// Unseals `unpadded_bytes_amount` bytes starting at `unpadded_byte_index` of
+/// the sector and writes them to `unseal_output_fd_raw`, which may be any
+/// writable file descriptor, including a pipe or a socket. The sealed sector
+/// descriptor must refer to a regular file, as the replica is memory mapped.
+/// Neither descriptor is closed.
 #[ffi_export]
 unsafe fn unseal_range(
     registered_proof: RegisteredSealProof,
@@ -322,15 +471,24 @@ unsafe fn unseal_range(
 ) -> repr_c::Box<UnsealRangeResponse> {
     catch_panic_response("unseal_range", || {
         use filepath::FilePath;
-        use std::os::unix::io::{FromRawFd, IntoRawFd};
 
-        let sealed_sector = fs::File::from_raw_fd(sealed_sector_fd_raw);
-        let mut unseal_output = fs::File::from_raw_fd(unseal_output_fd_raw);
+        ensure_valid_fd(sealed_sector_fd_raw, "sealed_sector_fd_raw")?;
+        ensure_valid_fd(unseal_output_fd_raw, "unseal_output_fd_raw")?;
+
+        // the file descriptors are owned by the caller and must stay open on
+        // every return path, including errors
+        let sealed_sector = FileDescriptorRef::new(sealed_sector_fd_raw);
+        let mut unseal_output = FileDescriptorRef::new(unseal_output_fd_raw);
+
+        let sealed_sector_path = sealed_sector
+            .as_file()
+            .path()
+            .context("failed to resolve the path of the sealed sector file descriptor")?;
 
         filecoin_proofs_api::seal::get_unsealed_range_mapped(
             registered_proof.into(),
             as_path_buf(&cache_dir_path)?,
-            sealed_sector.path().unwrap(),
+            sealed_sector_path,
             &mut unseal_output,
             *prover_id,
             SectorId::from(sector_id),
@@ -340,14 +498,120 @@ unsafe fn unseal_range(
             UnpaddedBytesAmount(unpadded_bytes_amount),
         )?;
 
-        // keep all file descriptors alive until unseal_range returns
-        let _ = sealed_sector.into_raw_fd();
-        let _ = unseal_output.into_raw_fd();
+        Ok(())
+    })
+}
+
+/// Like `unseal_range`, but fails unless the piece commitment of the unsealed
+/// bytes matches `expected_comm_p`. The range must cover exactly one piece, or
+/// the whole sector, in which case `expected_comm_p` is comm_d. The range is
+/// unsealed into an unlinked temporary file in `cache_dir_path`, which needs
+/// room for it, and only copied to `unseal_output_fd_raw` once it matches, so
+/// nothing is written to the output if the check fails.
+#[ffi_export]
+unsafe fn unseal_range_verified(
+    registered_proof: RegisteredSealProof,
+    cache_dir_path: c_slice::Ref<u8>,
+    sealed_sector_fd_raw: libc::c_int,
+    unseal_output_fd_raw: libc::c_int,
+    sector_id: u64,
+    prover_id: &[u8; 32],
+    ticket: &[u8; 32],
+    comm_d: &[u8; 32],
+    unpadded_byte_index: u64,
+    unpadded_bytes_amount: u64,
+    expected_comm_p: &[u8; 32],
+) -> repr_c::Box<UnsealRangeResponse> {
+    catch_panic_response("unseal_range_verified", || {
+        use filepath::FilePath;
+        use std::io::{Seek, SeekFrom};
+
+        ensure_valid_fd(sealed_sector_fd_raw, "sealed_sector_fd_raw")?;
+        ensure_valid_fd(unseal_output_fd_raw, "unseal_output_fd_raw")?;
+
+        let sector_bytes = get_max_user_bytes_per_staged_sector(registered_proof);
+        let in_sector = unpadded_byte_index
+            .checked_add(unpadded_bytes_amount)
+            .map_or(false, |end| end <= sector_bytes);
+        if !in_sector {
+            return Err(CallerError(format!(
+                "range of {} bytes at {} does not fit in the {} bytes of the sector",
+                unpadded_bytes_amount, unpadded_byte_index, sector_bytes
+            ))
+            .into());
+        }
+
+        let sealed_sector = FileDescriptorRef::new(sealed_sector_fd_raw);
+        let sealed_sector_path = sealed_sector
+            .as_file()
+            .path()
+            .context("failed to resolve the path of the sealed sector file descriptor")?;
+
+        let cache_dir_path = as_path_buf(&cache_dir_path)?;
+        let mut spool = helpers::create_unlinked_file_in(&cache_dir_path)?;
+
+        let (spool_writer, unsealed) = helpers::tee_to_reader(&mut spool);
+        let commitment = std::thread::spawn(move || {
+            let mut unsealed = unsealed;
+            let meta = seal::generate_piece_commitment(
+                registered_proof.into(),
+                &mut unsealed,
+                UnpaddedBytesAmount(unpadded_bytes_amount),
+            );
+            (meta, unsealed.reached_end())
+        });
+
+        // consumes the writer, so that the commitment sees the end of the range
+        let unseal_result = filecoin_proofs_api::seal::get_unsealed_range_mapped(
+            registered_proof.into(),
+            cache_dir_path,
+            sealed_sector_path,
+            spool_writer,
+            *prover_id,
+            SectorId::from(sector_id),
+            *comm_d,
+            *ticket,
+            UnpaddedByteIndex(unpadded_byte_index),
+            UnpaddedBytesAmount(unpadded_bytes_amount),
+        );
+        let (meta, reached_end) = commitment
+            .join()
+            .map_err(|_| anyhow!("piece commitment computation panicked"))?;
+        let meta = match meta {
+            // failed on its own: unsealing then only fails because its copy
+            // of the output was dropped
+            Err(err) if !reached_end => return Err(err),
+            // otherwise a failed unsealing is the cause of a failed commitment
+            meta => {
+                unseal_result?;
+                meta?
+            }
+        };
+        ensure!(
+            &meta.commitment == expected_comm_p,
+            "unsealed data does not match the expected commitment"
+        );
+
+        spool.seek(SeekFrom::Start(0))?;
+        std::io::copy(
+            &mut spool,
+            &mut FileDescriptorRef::new(unseal_output_fd_raw),
+        )?;
 
         Ok(())
     })
 }
 
+/// Sets the number of successful seal and PoSt verifications remembered in
+/// process, so that verifying the same proof with the same inputs again
+/// returns immediately. Failed verifications are never remembered. The least
+/// recently used entries are dropped first; 0, the default, disables the
+/// cache and clears it.
+#[ffi_export]
+fn set_verification_cache_size(size: u64) {
+    verification_cache::set_size(size);
+}
+
 /// Verifies the output of seal.
 #[ffi_export]
 fn verify_seal(
@@ -361,23 +625,124 @@ fn verify_seal(
     proof: c_slice::Ref<u8>,
 ) -> repr_c::Box<super::types::VerifySealResponse> {
     catch_panic_response("verify_seal", || {
+        ensure_seal_proof_len(registered_proof.into(), proof.len())?;
         let proof_bytes: Vec<u8> = proof.to_vec();
 
-        let result = seal::verify_seal(
-            registered_proof.into(),
-            *comm_r,
-            *comm_d,
-            *prover_id,
-            SectorId::from(sector_id),
-            *ticket,
-            *seed,
-            &proof_bytes,
+        let result = verification_cache::verify_cached(
+            || {
+                verification_cache::key(&[
+                    b"verify_seal",
+                    &(registered_proof as i32).to_le_bytes(),
+                    comm_r,
+                    comm_d,
+                    prover_id,
+                    ticket,
+                    seed,
+                    &sector_id.to_le_bytes(),
+                    &proof_bytes,
+                ])
+            },
+            || {
+                seal::verify_seal(
+                    registered_proof.into(),
+                    *comm_r,
+                    *comm_d,
+                    *prover_id,
+                    SectorId::from(sector_id),
+                    *ticket,
+                    *seed,
+                    &proof_bytes,
+                )
+            },
         )?;
 
         Ok(result)
     })
 }
 
+/// Verifies the output of seal, deriving comm_d from the provided pieces
+/// instead of trusting a precomputed value. A proof produced for a different
+/// set of pieces fails verification.
+#[ffi_export]
+fn verify_seal_with_pieces(
+    registered_proof: RegisteredSealProof,
+    comm_r: &[u8; 32],
+    pieces: c_slice::Ref<PublicPieceInfo>,
+    prover_id: &[u8; 32],
+    ticket: &[u8; 32],
+    seed: &[u8; 32],
+    sector_id: u64,
+    proof: c_slice::Ref<u8>,
+) -> repr_c::Box<super::types::VerifySealResponse> {
+    catch_panic_response("verify_seal_with_pieces", || {
+        ensure_seal_proof_len(registered_proof.into(), proof.len())?;
+        let public_pieces: Vec<PieceInfo> = pieces.iter().map(Into::into).collect();
+        let comm_d = seal::compute_comm_d(registered_proof.into(), &public_pieces)?;
+        let proof_bytes: Vec<u8> = proof.to_vec();
+
+        // keyed like verify_seal, which checks the same proof
+        let result = verification_cache::verify_cached(
+            || {
+                verification_cache::key(&[
+                    b"verify_seal",
+                    &(registered_proof as i32).to_le_bytes(),
+                    comm_r,
+                    &comm_d,
+                    prover_id,
+                    ticket,
+                    seed,
+                    &sector_id.to_le_bytes(),
+                    &proof_bytes,
+                ])
+            },
+            || {
+                seal::verify_seal(
+                    registered_proof.into(),
+                    *comm_r,
+                    comm_d,
+                    *prover_id,
+                    SectorId::from(sector_id),
+                    *ticket,
+                    *seed,
+                    &proof_bytes,
+                )
+            },
+        )?;
+
+        Ok(result)
+    })
+}
+
+/// Like `verify_seal`, but writes the result to `valid` and any error, truncated
+/// and NUL terminated, into the caller provided `err_buf`, so nothing has to be
+/// destroyed afterwards.
+#[ffi_export]
+fn verify_seal_with_err_buf(
+    registered_proof: RegisteredSealProof,
+    comm_r: &[u8; 32],
+    comm_d: &[u8; 32],
+    prover_id: &[u8; 32],
+    ticket: &[u8; 32],
+    seed: &[u8; 32],
+    sector_id: u64,
+    proof: c_slice::Ref<u8>,
+    err_buf: c_slice::Mut<u8>,
+    valid: &mut bool,
+) -> FCPResponseStatus {
+    let response = verify_seal(
+        registered_proof,
+        comm_r,
+        comm_d,
+        prover_id,
+        ticket,
+        seed,
+        sector_id,
+        proof,
+    );
+
+    write_response_into(response, err_buf, valid)
+}
+
 /// TODO: document
 #[ffi_export]
 fn generate_winning_post_sector_challenge(
@@ -467,6 +832,130 @@ fn generate_single_vanilla_proof(
     })
 }
 
+/// Checks that the sealed replicas and their caches can still be proven, the
+/// way a window PoSt would, without generating a SNARK. For every replica the
+/// file size is checked and a vanilla proof is generated for the fallback
+/// challenges derived from `randomness`, which reads the challenged nodes and
+/// their merkle paths from the sealed sector and tree_r_last, and the proof is
+/// then verified against the replica's comm_r. `randomness` is normalized with
+/// `derive_challenge_seed` first, so raw chain randomness can be passed and the
+/// challenges are those of a PoSt over the normalized seed. Invalid paths,
+/// truncated or missing files, unreadable trees and proofs that fail to verify
+/// are reported per sector; corruption is only detected in the challenged
+/// nodes, so callers should vary `randomness` between checks.
+#[ffi_export]
+fn check_sectors(
+    randomness: &[u8; 32],
+    replicas: c_slice::Ref<PrivateReplicaInfo>,
+    prover_id: &[u8; 32],
+) -> repr_c::Box<CheckSectorsResponse> {
+    catch_panic_response("check_sectors", || {
+        let randomness = &into_safe_challenge_seed(randomness);
+        let replicas: Vec<_> = replicas
+            .iter()
+            .map(|replica| {
+                (
+                    replica.sector_id,
+                    replica.registered_proof,
+                    replica.comm_r,
+                    as_path_buf(&replica.cache_dir_path),
+                    as_path_buf(&replica.replica_path),
+                )
+            })
+            .collect();
+
+        let checks: Vec<SectorCheck> = replicas
+            .into_par_iter()
+            .map(
+                |(sector_id, registered_proof, comm_r, cache_dir_path, replica_path)| {
+                    let result = match (cache_dir_path, replica_path) {
+                        (Ok(cache_dir_path), Ok(replica_path)) => check_sector(
+                            randomness,
+                            registered_proof.into(),
+                            SectorId::from(sector_id),
+                            comm_r,
+                            cache_dir_path,
+                            replica_path,
+                            prover_id,
+                        ),
+                        (Err(err), _) => {
+                            Err(anyhow::Error::new(err).context("invalid cache_dir_path"))
+                        }
+                        (_, Err(err)) => {
+                            Err(anyhow::Error::new(err).context("invalid replica_path"))
+                        }
+                    };
+
+                    SectorCheck {
+                        sector_id,
+                        ok: result.is_ok(),
+                        error_msg: match result {
+                            Ok(()) => Default::default(),
+                            Err(err) => error_message(&err).into_bytes().into_boxed_slice().into(),
+                        },
+                    }
+                },
+            )
+            .collect();
+
+        Ok(checks.into_boxed_slice().into())
+    })
+}
+
+fn check_sector(
+    randomness: &[u8; 32],
+    registered_proof: api::RegisteredPoStProof,
+    sector_id: SectorId,
+    comm_r: [u8; 32],
+    cache_dir_path: std::path::PathBuf,
+    replica_path: std::path::PathBuf,
+    prover_id: &[u8; 32],
+) -> anyhow::Result<()> {
+    let sector_size = u64::from(registered_proof.sector_size());
+    let replica_size = fs::metadata(&replica_path)
+        .with_context(|| format!("could not stat sealed sector {:?}", replica_path))?
+        .len();
+    ensure!(
+        replica_size == sector_size,
+        "sealed sector {:?} is {} bytes, expected {}",
+        replica_path,
+        replica_size,
+        sector_size
+    );
+
+    let mut challenges = filecoin_proofs_api::post::generate_fallback_sector_challenges(
+        registered_proof,
+        randomness,
+        &[sector_id],
+        *prover_id,
+    )?;
+    let challenges = challenges
+        .remove(&sector_id)
+        .context("no challenges generated for sector")?;
+
+    let replica =
+        api::PrivateReplicaInfo::new(registered_proof, comm_r, cache_dir_path, replica_path);
+    let vanilla_proof = filecoin_proofs_api::post::generate_single_vanilla_proof(
+        registered_proof,
+        sector_id,
+        &replica,
+        &challenges,
+    )?;
+    ensure!(
+        helpers::verify_single_vanilla_proof(
+            registered_proof,
+            randomness,
+            prover_id,
+            sector_id,
+            &comm_r,
+            &vanilla_proof,
+        )?,
+        "vanilla proof does not verify against comm_r"
+    );
+
+    Ok(())
+}
+
 /// TODO: document
 #[ffi_export]
 fn generate_winning_post_with_vanilla(
@@ -524,7 +1013,9 @@ fn generate_winning_post(
     })
 }
 
-/// Verifies that a proof-of-spacetime is valid.
+/// Verifies that a proof-of-spacetime is valid. `randomness` is normalized
+/// with `derive_challenge_seed` first, so raw chain randomness can be passed
+/// and proofs made over the normalized seed verify.
 #[ffi_export]
 fn verify_winning_post(
     randomness: &[u8; 32],
@@ -533,21 +1024,52 @@ fn verify_winning_post(
     prover_id: &[u8; 32],
 ) -> repr_c::Box<VerifyWinningPoStResponse> {
     catch_panic_response("verify_winning_post", || {
-        let replicas = to_public_replica_info_map(replicas);
+        let randomness = &into_safe_challenge_seed(randomness);
+        let key = || {
+            post_verification_key(
+                b"verify_winning_post",
+                randomness,
+                replicas,
+                proofs,
+                prover_id,
+            )
+        };
+        let replicas = to_public_replica_info_map(replicas)?;
+        for pp in proofs.iter() {
+            ensure_post_proof_len(pp.registered_proof, pp.proof.len())?;
+        }
         let proofs: Vec<u8> = proofs
             .iter()
             .flat_map(|pp| &pp.proof[..])
             .copied()
             .collect();
 
-        let result = filecoin_proofs_api::post::verify_winning_post(
-            randomness, &proofs, &replicas, *prover_id,
-        )?;
+        let result = verification_cache::verify_cached(key, || {
+            filecoin_proofs_api::post::verify_winning_post(
+                randomness, &proofs, &replicas, *prover_id,
+            )
+        })?;
 
         Ok(result)
     })
 }
 
+/// Like `verify_winning_post`, but reports through caller provided memory, see
+/// `verify_seal_with_err_buf`.
+#[ffi_export]
+fn verify_winning_post_with_err_buf(
+    randomness: &[u8; 32],
+    replicas: c_slice::Ref<PublicReplicaInfo>,
+    proofs: c_slice::Ref<PoStProof>,
+    prover_id: &[u8; 32],
+    err_buf: c_slice::Mut<u8>,
+    valid: &mut bool,
+) -> FCPResponseStatus {
+    let response = verify_winning_post(randomness, replicas, proofs, prover_id);
+
+    write_response_into(response, err_buf, valid)
+}
+
 /// TODO: document
 #[ffi_export]
 fn generate_window_post_with_vanilla(
@@ -562,12 +1084,14 @@ fn generate_window_post_with_vanilla(
             .map(|vanilla_proof| vanilla_proof.to_vec())
             .collect();
 
-        let result = filecoin_proofs_api::post::generate_window_post_with_vanilla(
-            registered_proof.into(),
-            randomness,
-            *prover_id,
-            &vanilla_proofs,
-        );
+        let result = limiter::acquire(Job::WindowPoSt).and_then(|_job| {
+            filecoin_proofs_api::post::generate_window_post_with_vanilla(
+                registered_proof.into(),
+                randomness,
+                *prover_id,
+                &vanilla_proofs,
+            )
+        });
 
         let mut response = GenerateWindowPoStResponse::default();
 
@@ -597,8 +1121,8 @@ fn generate_window_post_with_vanilla(
                     response.value.faulty_sectors = sectors_u64.into_boxed_slice().into()
                 }
 
-                response.status_code = FCPResponseStatus::UnclassifiedError;
-                response.error_msg = err.to_string().into_bytes().into_boxed_slice().into();
+                response.status_code = error_status(&err);
+                response.error_msg = error_message(&err).into_bytes().into_boxed_slice().into();
             }
         }
 
@@ -614,7 +1138,8 @@ fn generate_window_post(
     prover_id: &[u8; 32],
 ) -> repr_c::Box<GenerateWindowPoStResponse> {
     catch_panic_response_raw("generate_window_post", || {
-        let result = to_private_replica_info_map(replicas).and_then(|replicas| {
+        let result = limiter::acquire(Job::WindowPoSt).and_then(|_job| {
+            let replicas = to_private_replica_info_map(replicas)?;
             filecoin_proofs_api::post::generate_window_post(randomness, &replicas, *prover_id)
         });
 
@@ -645,8 +1170,8 @@ fn generate_window_post(
                     response.value.faulty_sectors = sectors_u64.into_boxed_slice().into();
                 }
 
-                response.status_code = FCPResponseStatus::UnclassifiedError;
-                response.error_msg = err.to_string().into_bytes().into_boxed_slice().into();
+                response.status_code = error_status(&err);
+                response.error_msg = error_message(&err).into_bytes().into_boxed_slice().into();
             }
         }
 
@@ -654,7 +1179,9 @@ fn generate_window_post(
     })
 }
 
-/// Verifies that a proof-of-spacetime is valid.
+/// Verifies that a proof-of-spacetime is valid. `randomness` is normalized
+/// with `derive_challenge_seed` first, so raw chain randomness can be passed
+/// and proofs made over the normalized seed verify.
 #[ffi_export]
 fn verify_window_post(
     randomness: &[u8; 32],
@@ -663,7 +1190,20 @@ fn verify_window_post(
     prover_id: &[u8; 32],
 ) -> repr_c::Box<VerifyWindowPoStResponse> {
     catch_panic_response("verify_window_post", || {
-        let replicas = to_public_replica_info_map(replicas);
+        let randomness = &into_safe_challenge_seed(randomness);
+        let key = || {
+            post_verification_key(
+                b"verify_window_post",
+                randomness,
+                replicas,
+                proofs,
+                prover_id,
+            )
+        };
+        let replicas = to_public_replica_info_map(replicas)?;
+        for pp in proofs.iter() {
+            ensure_post_proof_len(pp.registered_proof, pp.proof.len())?;
+        }
         let proofs: Vec<(api::RegisteredPoStProof, &[u8])> = proofs
             .iter()
             .map(|x| {
@@ -674,14 +1214,55 @@ fn verify_window_post(
             })
             .collect();
 
-        let result = filecoin_proofs_api::post::verify_window_post(
-            randomness, &proofs, &replicas, *prover_id,
-        )?;
+        let result = verification_cache::verify_cached(key, || {
+            filecoin_proofs_api::post::verify_window_post(
+                randomness, &proofs, &replicas, *prover_id,
+            )
+        })?;
 
         Ok(result)
     })
 }
 
+/// Builds the verification cache key for a PoSt with the given inputs.
+fn post_verification_key(
+    name: &[u8],
+    randomness: &[u8; 32],
+    replicas: c_slice::Ref<PublicReplicaInfo>,
+    proofs: c_slice::Ref<PoStProof>,
+    prover_id: &[u8; 32],
+) -> Vec<u8> {
+    let mut parts: Vec<Vec<u8>> = vec![name.to_vec(), randomness.to_vec(), prover_id.to_vec()];
+    for replica in replicas.iter() {
+        parts.push((replica.registered_proof as i32).to_le_bytes().to_vec());
+        parts.push(replica.comm_r.to_vec());
+        parts.push(replica.sector_id.to_le_bytes().to_vec());
+    }
+    for proof in proofs.iter() {
+        parts.push((proof.registered_proof as i32).to_le_bytes().to_vec());
+        parts.push(proof.proof.to_vec());
+    }
+
+    let parts: Vec<&[u8]> = parts.iter().map(|part| &part[..]).collect();
+    verification_cache::key(&parts)
+}
+
+/// Like `verify_window_post`, but reports through caller provided memory, see
+/// `verify_seal_with_err_buf`.
+#[ffi_export]
+fn verify_window_post_with_err_buf(
+    randomness: &[u8; 32],
+    replicas: c_slice::Ref<PublicReplicaInfo>,
+    proofs: c_slice::Ref<PoStProof>,
+    prover_id: &[u8; 32],
+    err_buf: c_slice::Mut<u8>,
+    valid: &mut bool,
+) -> FCPResponseStatus {
+    let response = verify_window_post(randomness, replicas, proofs, prover_id);
+
+    write_response_into(response, err_buf, valid)
+}
+
 /// TODO: document
 #[ffi_export]
 fn merge_window_post_partition_proofs(
@@ -736,13 +1317,15 @@ fn generate_single_window_post_with_vanilla(
             .map(|vanilla_proof| vanilla_proof.to_vec())
             .collect();
 
-        let result = filecoin_proofs_api::post::generate_single_window_post_with_vanilla(
-            registered_proof.into(),
-            randomness,
-            *prover_id,
-            &vanilla_proofs,
-            partition_index,
-        );
+        let result = limiter::acquire(Job::WindowPoSt).and_then(|_job| {
+            filecoin_proofs_api::post::generate_single_window_post_with_vanilla(
+                registered_proof.into(),
+                randomness,
+                *prover_id,
+                &vanilla_proofs,
+                partition_index,
+            )
+        });
 
         let mut response = GenerateSingleWindowPoStWithVanillaResponse::default();
 
@@ -769,8 +1352,8 @@ fn generate_single_window_post_with_vanilla(
                     response.value.faulty_sectors = sectors_u64.into_boxed_slice().into();
                 }
 
-                response.status_code = FCPResponseStatus::UnclassifiedError;
-                response.error_msg = err.to_string().into_bytes().into_boxed_slice().into();
+                response.status_code = error_status(&err);
+                response.error_msg = error_message(&err).into_bytes().into_boxed_slice().into();
             }
         }
 
@@ -1000,6 +1583,29 @@ fn verify_empty_sector_update_proof(
     })
 }
 
+/// Like `verify_empty_sector_update_proof`, but reports through caller provided
+/// memory, see `verify_seal_with_err_buf`.
+#[ffi_export]
+fn verify_empty_sector_update_proof_with_err_buf(
+    registered_proof: RegisteredUpdateProof,
+    proof: c_slice::Ref<u8>,
+    comm_r_old: &[u8; 32],
+    comm_r_new: &[u8; 32],
+    comm_d_new: &[u8; 32],
+    err_buf: c_slice::Mut<u8>,
+    valid: &mut bool,
+) -> FCPResponseStatus {
+    let response = verify_empty_sector_update_proof(
+        registered_proof,
+        proof,
+        comm_r_old,
+        comm_r_new,
+        comm_d_new,
+    );
+
+    write_response_into(response, err_buf, valid)
+}
+
 /// Returns the merkle root for a piece after piece padding and alignment.
 /// The caller is responsible for closing the passed in file descriptor.
 #[ffi_export]
@@ -1011,6 +1617,8 @@ unsafe fn generate_piece_commitment(
     catch_panic_response("generate_piece_commitment", || {
         use std::os::unix::io::{FromRawFd, IntoRawFd};
 
+        ensure_valid_fd(piece_fd_raw, "piece_fd_raw")?;
+
         let mut piece_file = fs::File::from_raw_fd(piece_fd_raw);
 
         let unpadded_piece_size = UnpaddedBytesAmount(unpadded_piece_size);
@@ -1032,38 +1640,504 @@ unsafe fn generate_piece_commitment(
     })
 }
 
-/// Returns the merkle root for a sector containing the provided pieces.
+/// Returns the alignment `write_with_alignment` applies when writing a piece of
+/// `piece_size` unpadded bytes after the pieces in `existing_piece_sizes`, and
+/// the padded size the piece occupies in the sector. Fails if any piece size is
+/// not a power of two multiple of 127, which `write_with_alignment` rejects,
+/// or if the piece does not fit in the sector.
+#[ffi_export]
+fn get_piece_alignment(
+    registered_proof: RegisteredSealProof,
+    existing_piece_sizes: c_slice::Ref<u64>,
+    piece_size: u64,
+) -> repr_c::Box<GetPieceAlignmentResponse> {
+    catch_panic_response("get_piece_alignment", || {
+        let sector_size = get_max_user_bytes_per_staged_sector(registered_proof);
+        // checked against the sector size first, so that the alignment math
+        // can't overflow
+        for &size in existing_piece_sizes
+            .iter()
+            .chain(std::iter::once(&piece_size))
+        {
+            ensure!(
+                size <= sector_size,
+                "invalid unpadded piece size {}: larger than the {} bytes of the sector",
+                size,
+                sector_size
+            );
+            let padded_size = u64::from(PaddedBytesAmount::from(UnpaddedBytesAmount(size)));
+            ensure!(
+                padded_size >= 128
+                    && padded_size.is_power_of_two()
+                    && padded_to_unpadded(padded_size) == size,
+                "invalid unpadded piece size {}: must be a power of two multiple of 127",
+                size
+            );
+        }
+
+        let written = sum_piece_bytes_with_alignment(&existing_piece_sizes);
+        let (left, right) = helpers::get_piece_alignment(written, piece_size);
+
+        ensure!(
+            written + left + piece_size + right <= sector_size,
+            "piece of {} bytes does not fit in the sector: {} of {} bytes already used",
+            piece_size,
+            written,
+            sector_size
+        );
+
+        Ok(PieceAlignment {
+            left_alignment_unpadded: left,
+            right_alignment_unpadded: right,
+            padded_piece_size: unpadded_to_padded(piece_size + right),
+        })
+    })
+}
+
+/// Returns the merkle root for a piece held in memory, after fr32 padding it.
+/// Pieces must be a power of two multiple of 127 bytes, so `piece` is first
+/// padded with zeros up to the next such size, at least 127 bytes, the way
+/// `add_piece` pads it in the sector; `num_bytes_aligned` is the padded size.
+/// Fails with a caller error if the padded piece doesn't fit in a sector.
+#[ffi_export]
+fn generate_piece_commitment_from_bytes(
+    registered_proof: RegisteredSealProof,
+    piece: c_slice::Ref<u8>,
+) -> repr_c::Box<GeneratePieceCommitmentResponse> {
+    catch_panic_response("generate_piece_commitment_from_bytes", || {
+        use std::io::Read;
+
+        let registered_proof = api::RegisteredSealProof::from(registered_proof);
+        let max_piece_size = padded_to_unpadded(u64::from(registered_proof.sector_size()));
+        let piece_size = piece.len() as u64;
+        if piece_size > max_piece_size {
+            return Err(CallerError(format!(
+                "piece of {} bytes is larger than the {} bytes a sector holds",
+                piece_size, max_piece_size
+            ))
+            .into());
+        }
+
+        let (_, right) = helpers::get_piece_alignment(0, piece_size);
+        let padded = piece[..].chain(std::io::repeat(0).take(right));
+        let meta = seal::generate_piece_commitment(
+            registered_proof,
+            padded,
+            UnpaddedBytesAmount(piece_size + right),
+        )?;
+
+        Ok(GeneratePieceCommitment {
+            comm_p: meta.commitment,
+            num_bytes_aligned: meta.size.into(),
+        })
+    })
+}
+
+/// Computes the piece commitments of the files at `piece_paths` in parallel.
+/// `unpadded_piece_sizes` holds the size of each piece, in the same order.
+/// Fails if any of the commitments can not be computed.
+#[ffi_export]
+fn generate_piece_commitments(
+    registered_proof: RegisteredSealProof,
+    piece_paths: c_slice::Ref<c_slice::Box<u8>>,
+    unpadded_piece_sizes: c_slice::Ref<u64>,
+) -> repr_c::Box<GeneratePieceCommitmentsResponse> {
+    catch_panic_response("generate_piece_commitments", || {
+        ensure!(
+            piece_paths.len() == unpadded_piece_sizes.len(),
+            "got {} piece paths but {} piece sizes",
+            piece_paths.len(),
+            unpadded_piece_sizes.len()
+        );
+
+        let registered_proof: api::RegisteredSealProof = registered_proof.into();
+        let pieces = piece_paths
+            .iter()
+            .zip(unpadded_piece_sizes.iter())
+            .map(|(path, size)| Ok((as_path_buf(path)?, UnpaddedBytesAmount(*size))))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let commitments = pieces
+            .into_par_iter()
+            .map(|(path, size)| {
+                let mut piece_file = fs::File::open(&path)
+                    .with_context(|| format!("failed to open piece {:?}", path))?;
+                let meta = seal::generate_piece_commitment(registered_proof, &mut piece_file, size)
+                    .with_context(|| format!("failed to generate commitment for {:?}", path))?;
+
+                Ok(GeneratePieceCommitment {
+                    comm_p: meta.commitment,
+                    num_bytes_aligned: meta.size.into(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(commitments.into_boxed_slice().into())
+    })
+}
+
+/// Returns the merkle root (comm_d) for a sector containing the provided
+/// pieces. Only the size and comm_p of each piece are used; the sector size is
+/// derived from `registered_proof`. Pieces that do not fill the sector are
+/// padded with zero pieces.
+#[ffi_export]
+fn generate_data_commitment(
+    registered_proof: RegisteredSealProof,
+    pieces: c_slice::Ref<PublicPieceInfo>,
+) -> repr_c::Box<GenerateDataCommitmentResponse> {
+    catch_panic_response("generate_data_commitment", || {
+        let public_pieces: Vec<PieceInfo> = pieces.iter().map(Into::into).collect();
+        let result = seal::compute_comm_d(registered_proof.into(), &public_pieces)?;
+
+        Ok(result)
+    })
+}
+
+/// Returns the CID encoding of a raw commitment.
+#[ffi_export]
+fn commitment_to_cid(
+    kind: CommitmentKind,
+    commitment: &[u8; 32],
+) -> repr_c::Box<CommitmentToCidResponse> {
+    catch_panic_response("commitment_to_cid", || {
+        let cid = match kind {
+            CommitmentKind::Sealed => commcid::replica_commitment_v1_to_cid(commitment),
+            CommitmentKind::Unsealed => commcid::data_commitment_v1_to_cid(commitment),
+        }
+        .map_err(|err| anyhow!("invalid commitment: {}", err))?;
+
+        Ok(cid.to_bytes().into_boxed_slice().into())
+    })
+}
+
+/// Returns the raw commitment encoded in the provided CID, checking that its
+/// codec and multihash match the given kind.
+#[ffi_export]
+fn cid_to_commitment(
+    kind: CommitmentKind,
+    cid: c_slice::Ref<u8>,
+) -> repr_c::Box<CidToCommitmentResponse> {
+    catch_panic_response("cid_to_commitment", || {
+        let cid = Cid::try_from(&cid[..]).map_err(|err| anyhow!("invalid cid: {}", err))?;
+        let commitment = match kind {
+            CommitmentKind::Sealed => commcid::cid_to_replica_commitment_v1(&cid),
+            CommitmentKind::Unsealed => commcid::cid_to_data_commitment_v1(&cid),
+        }
+        .map_err(|err| anyhow!("invalid commitment cid: {}", err))?;
+
+        Ok(commitment)
+    })
+}
+
+#[ffi_export]
+fn clear_cache(
+    sector_size: u64,
+    cache_dir_path: c_slice::Ref<u8>,
+) -> repr_c::Box<ClearCacheResponse> {
+    catch_panic_response("clear_cache", || {
+        let result = seal::clear_cache(sector_size, &as_path_buf(&cache_dir_path)?)?;
+
+        Ok(result)
+    })
+}
+
+/// Creates the staged or unsealed sector file at `path` with the padded size of
+/// `registered_proof`, failing if the file already exists. The file reads as
+/// zeros, i.e. as zero pieces, until pieces are written over it. With
+/// `preallocate` the blocks are also reserved on disk, so that running out of
+/// space is reported here rather than halfway through sealing; otherwise the
+/// file is sparse.
+#[ffi_export]
+fn create_sector_file(
+    registered_proof: RegisteredSealProof,
+    path: c_slice::Ref<u8>,
+    preallocate: bool,
+) -> repr_c::Box<CreateSectorFileResponse> {
+    catch_panic_response("create_sector_file", || {
+        let path = as_path_buf(&path)?;
+        let sector_size = u64::from(api::RegisteredSealProof::from(registered_proof).sector_size());
+
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .with_context(|| format!("could not create sector file {:?}", path))?;
+
+        let sized = file.set_len(sector_size).map_err(Into::into).and_then(|_| {
+            if preallocate {
+                helpers::preallocate(&file, sector_size)
+                    .with_context(|| format!("could not preallocate {:?}", path))
+            } else {
+                Ok(())
+            }
+        });
+        if sized.is_err() {
+            // so that a retry isn't refused because the file exists
+            drop(file);
+            let _ = fs::remove_file(&path);
+        }
+
+        sized
+    })
+}
+
+/// Returns the number of user bytes that will fit into a staged sector.
+#[ffi_export]
+fn get_max_user_bytes_per_staged_sector(registered_proof: RegisteredSealProof) -> u64 {
+    u64::from(UnpaddedBytesAmount::from(
+        api::RegisteredSealProof::from(registered_proof).sector_size(),
+    ))
+}
+
+/// Returns the sector sizes this build can seal and prove, ordered from the
+/// smallest, with the current seal proof and the PoSt proofs for each.
+#[ffi_export]
+fn get_supported_sector_sizes() -> repr_c::Box<GetSupportedSectorSizesResponse> {
+    catch_panic_response("get_supported_sector_sizes", || {
+        use RegisteredPoStProof::*;
+        use RegisteredSealProof::*;
+
+        let proofs = [
+            (
+                StackedDrg2KiBV1_1,
+                StackedDrgWinning2KiBV1,
+                StackedDrgWindow2KiBV1,
+            ),
+            (
+                StackedDrg8MiBV1_1,
+                StackedDrgWinning8MiBV1,
+                StackedDrgWindow8MiBV1,
+            ),
+            (
+                StackedDrg512MiBV1_1,
+                StackedDrgWinning512MiBV1,
+                StackedDrgWindow512MiBV1,
+            ),
+            (
+                StackedDrg32GiBV1_1,
+                StackedDrgWinning32GiBV1,
+                StackedDrgWindow32GiBV1,
+            ),
+            (
+                StackedDrg64GiBV1_1,
+                StackedDrgWinning64GiBV1,
+                StackedDrgWindow64GiBV1,
+            ),
+        ];
+
+        let sizes: Vec<SupportedSectorSize> = proofs
+            .iter()
+            .map(|&(seal, winning, window)| SupportedSectorSize {
+                sector_size: u64::from(api::RegisteredSealProof::from(seal).sector_size()),
+                registered_seal_proof: seal,
+                registered_winning_post_proof: winning,
+                registered_window_post_proof: window,
+            })
+            .collect();
+
+        Ok(sizes.into_boxed_slice().into())
+    })
+}
+
+/// Seals, proves and verifies a 2KiB sector in a temporary directory, reporting
+/// the status and duration of every stage. Stages after the first failure are
+/// not run. The 2KiB parameters must be present in the parameter cache.
+#[ffi_export]
+fn run_self_test() -> repr_c::Box<RunSelfTestResponse> {
+    catch_panic_response("run_self_test", || {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "filcrypto-self-test-{}-{}",
+            std::process::id(),
+            nonce
+        ));
+        fs::create_dir_all(&dir)?;
+
+        let mut stages = Vec::new();
+        // failures are recorded in the stages themselves
+        let _ = run_self_test_stages(&dir, &mut stages);
+        let _ = fs::remove_dir_all(&dir);
+
+        Ok(stages.into_boxed_slice().into())
+    })
+}
+
+fn self_test_stage<T>(
+    stages: &mut Vec<SelfTestStage>,
+    name: &str,
+    stage: impl FnOnce() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let start = std::time::Instant::now();
+    let result = stage();
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let (status_code, error_msg) = match &result {
+        Ok(_) => (FCPResponseStatus::NoError, Vec::new()),
+        Err(err) => (error_status(err), error_message(err).into_bytes()),
+    };
+    stages.push(SelfTestStage {
+        name: name.as_bytes().to_vec().into_boxed_slice().into(),
+        status_code,
+        error_msg: error_msg.into_boxed_slice().into(),
+        duration_ms,
+    });
+
+    result
+}
+
+fn run_self_test_stages(
+    dir: &std::path::Path,
+    stages: &mut Vec<SelfTestStage>,
+) -> anyhow::Result<()> {
+    let registered_proof = api::RegisteredSealProof::StackedDrg2KiBV1_1;
+    let cache_dir = dir.join("cache");
+    let staged_path = dir.join("staged");
+    let sealed_path = dir.join("sealed");
+    let prover_id = [1u8; 32];
+    let sector_id = SectorId::from(42);
+    let ticket = [2u8; 32];
+    let seed = [3u8; 32];
+
+    let piece = self_test_stage(stages, "add_piece", || {
+        let piece_size = UnpaddedBytesAmount::from(registered_proof.sector_size());
+        let data = vec![7u8; u64::from(piece_size) as usize];
+
+        fs::create_dir_all(&cache_dir)?;
+        fs::File::create(&sealed_path)?;
+        let staged = fs::File::create(&staged_path)?;
+
+        let (info, _) = seal::add_piece(registered_proof, &data[..], staged, piece_size, &[])?;
+        Ok(info)
+    })?;
+    let pieces = [piece];
+
+    let phase1 = self_test_stage(stages, "seal_pre_commit_phase1", || {
+        seal::seal_pre_commit_phase1(
+            registered_proof,
+            &cache_dir,
+            &staged_path,
+            &sealed_path,
+            prover_id,
+            sector_id,
+            ticket,
+            &pieces,
+        )
+    })?;
+
+    let phase2 = self_test_stage(stages, "seal_pre_commit_phase2", || {
+        seal::seal_pre_commit_phase2(phase1, &cache_dir, &sealed_path)
+    })?;
+    let (comm_r, comm_d) = (phase2.comm_r, phase2.comm_d);
+
+    let commit1 = self_test_stage(stages, "seal_commit_phase1", || {
+        seal::seal_commit_phase1(
+            &cache_dir,
+            &sealed_path,
+            prover_id,
+            sector_id,
+            ticket,
+            seed,
+            phase2,
+            &pieces,
+        )
+    })?;
+
+    let commit2 = self_test_stage(stages, "seal_commit_phase2", || {
+        seal::seal_commit_phase2(commit1, prover_id, sector_id)
+    })?;
+
+    self_test_stage(stages, "verify_seal", || {
+        let valid = seal::verify_seal(
+            registered_proof,
+            comm_r,
+            comm_d,
+            prover_id,
+            sector_id,
+            ticket,
+            seed,
+            &commit2.proof,
+        )?;
+        anyhow::ensure!(valid, "proof was not valid");
+
+        Ok(())
+    })
+}
+
+/// Returns `randomness` with its two most significant bits cleared, making it
+/// a canonical field element. `check_sectors`, `verify_winning_post` and
+/// `verify_window_post` apply this themselves; randomness passed to the other
+/// PoSt functions must already be in this form, otherwise it may be rejected as
+/// a non-canonical field element.
+#[ffi_export]
+fn derive_challenge_seed(randomness: &[u8; 32]) -> [u8; 32] {
+    into_safe_challenge_seed(randomness)
+}
+
+/// Returns the number of unpadded bytes stored in `padded` fr32 padded bytes.
+#[ffi_export]
+fn padded_to_unpadded(padded: u64) -> u64 {
+    u64::from(UnpaddedBytesAmount::from(PaddedBytesAmount(padded)))
+}
+
+/// Returns the number of fr32 padded bytes needed to store `unpadded` bytes.
+#[ffi_export]
+fn unpadded_to_padded(unpadded: u64) -> u64 {
+    u64::from(PaddedBytesAmount::from(UnpaddedBytesAmount(unpadded)))
+}
+
+/// Returns the number of partitions in a seal proof of the provided type.
+#[ffi_export]
+fn get_seal_partitions(registered_proof: RegisteredSealProof) -> u64 {
+    let config = api::RegisteredSealProof::from(registered_proof).as_v1_config();
+    usize::from(config.partitions) as u64
+}
+
+/// Returns the length in bytes of a seal proof of the provided type.
+#[ffi_export]
+fn get_seal_proof_len(registered_proof: RegisteredSealProof) -> u64 {
+    helpers::seal_proof_len(registered_proof.into()) as u64
+}
+
+/// Returns the length in bytes of a winning PoSt proof of the provided type,
+/// or of each partition of a window PoSt proof. A window PoSt proof is this
+/// length times `get_num_partition_for_fallback_post` for the number of
+/// sectors proven.
 #[ffi_export]
-fn generate_data_commitment(
-    registered_proof: RegisteredSealProof,
-    pieces: c_slice::Ref<PublicPieceInfo>,
-) -> repr_c::Box<GenerateDataCommitmentResponse> {
-    catch_panic_response("generate_data_commitment", || {
-        let public_pieces: Vec<PieceInfo> = pieces.iter().map(Into::into).collect();
-        let result = seal::compute_comm_d(registered_proof.into(), &public_pieces)?;
+fn get_post_proof_len(_registered_proof: RegisteredPoStProof) -> u64 {
+    // every PoSt partition is a single groth16 proof, for all proof types
+    SINGLE_PARTITION_PROOF_LEN as u64
+}
 
-        Ok(result)
-    })
+/// Returns the number of challenges per sector in a PoSt of the provided type.
+#[ffi_export]
+fn get_post_challenge_count(registered_proof: RegisteredPoStProof) -> u64 {
+    let config = api::RegisteredPoStProof::from(registered_proof).as_v1_config();
+    config.challenge_count as u64
 }
 
+/// Returns the number of sectors proven by each partition of a PoSt of the
+/// provided type. Winning PoSt always has a single partition.
 #[ffi_export]
-fn clear_cache(
-    sector_size: u64,
-    cache_dir_path: c_slice::Ref<u8>,
-) -> repr_c::Box<ClearCacheResponse> {
-    catch_panic_response("clear_cache", || {
-        let result = seal::clear_cache(sector_size, &as_path_buf(&cache_dir_path)?)?;
+fn get_post_sectors_per_partition(registered_proof: RegisteredPoStProof) -> u64 {
+    let config = api::RegisteredPoStProof::from(registered_proof).as_v1_config();
+    config.sector_count as u64
+}
 
-        Ok(result)
-    })
+/// Returns the number of challenges in each partition of a PoSt of the
+/// provided type.
+#[ffi_export]
+fn get_post_challenges_per_partition(registered_proof: RegisteredPoStProof) -> u64 {
+    let config = api::RegisteredPoStProof::from(registered_proof).as_v1_config();
+    (config.challenge_count * config.sector_count) as u64
 }
 
-/// Returns the number of user bytes that will fit into a staged sector.
+/// Returns the length in bytes of a single partition proof. Seal proofs and
+/// window PoSt proofs are a concatenation of such partition proofs.
 #[ffi_export]
-fn get_max_user_bytes_per_staged_sector(registered_proof: RegisteredSealProof) -> u64 {
-    u64::from(UnpaddedBytesAmount::from(
-        api::RegisteredSealProof::from(registered_proof).sector_size(),
-    ))
+fn get_single_partition_proof_len() -> u64 {
+    SINGLE_PARTITION_PROOF_LEN as u64
 }
 
 /// Returns the CID of the Groth parameter file for sealing.
@@ -1184,7 +2258,7 @@ fn registered_seal_proof_accessor(
 ) -> repr_c::Box<StringResponse> {
     let rsp: api::RegisteredSealProof = registered_proof.into();
 
-    repr_c::Box::new(StringResponse::from(
+    new_response(StringResponse::from(
         op(rsp).map(|v| v.into_bytes().into_boxed_slice().into()),
     ))
 }
@@ -1195,7 +2269,7 @@ fn registered_post_proof_accessor(
 ) -> repr_c::Box<StringResponse> {
     let rsp: api::RegisteredPoStProof = registered_proof.into();
 
-    repr_c::Box::new(StringResponse::from(
+    new_response(StringResponse::from(
         op(rsp).map(|v| v.into_bytes().into_boxed_slice().into()),
     ))
 }
@@ -1235,6 +2309,23 @@ destructor!(
     GenerateDataCommitmentResponse
 );
 destructor!(destroy_string_response, StringResponse);
+destructor!(destroy_write_fr32_response, WriteFr32Response);
+destructor!(destroy_run_self_test_response, RunSelfTestResponse);
+destructor!(
+    destroy_get_supported_sector_sizes_response,
+    GetSupportedSectorSizesResponse
+);
+destructor!(
+    destroy_generate_piece_commitments_response,
+    GeneratePieceCommitmentsResponse
+);
+destructor!(destroy_add_piece_response, AddPieceResponse);
+destructor!(
+    destroy_get_piece_alignment_response,
+    GetPieceAlignmentResponse
+);
+destructor!(destroy_commitment_to_cid_response, CommitmentToCidResponse);
+destructor!(destroy_cid_to_commitment_response, CidToCommitmentResponse);
 destructor!(destroy_verify_seal_response, VerifySealResponse);
 destructor!(
     destroy_verify_aggregate_seal_response,
@@ -1282,6 +2373,11 @@ destructor!(
     GenerateWinningPoStSectorChallenge
 );
 destructor!(destroy_clear_cache_response, ClearCacheResponse);
+destructor!(destroy_check_sectors_response, CheckSectorsResponse);
+destructor!(
+    destroy_create_sector_file_response,
+    CreateSectorFileResponse
+);
 destructor!(destroy_aggregate_proof, AggregateProof);
 destructor!(
     destroy_empty_sector_update_generate_proof_response,
@@ -1419,6 +2515,144 @@ pub mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_sector_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("staged");
+
+        for preallocate in [false, true] {
+            let resp = create_sector_file(
+                RegisteredSealProof::StackedDrg2KiBV1_1,
+                as_bytes(&path).into(),
+                preallocate,
+            );
+            if resp.status_code != FCPResponseStatus::NoError {
+                let msg = str::from_utf8(&resp.error_msg).unwrap();
+                panic!("create_sector_file failed: {:?}", msg);
+            }
+            destroy_create_sector_file_response(resp);
+
+            assert_eq!(fs::read(&path)?, vec![0u8; 2048]);
+
+            // existing files are never truncated
+            let resp = create_sector_file(
+                RegisteredSealProof::StackedDrg2KiBV1_1,
+                as_bytes(&path).into(),
+                preallocate,
+            );
+            assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+            destroy_create_sector_file_response(resp);
+
+            fs::remove_file(&path)?;
+        }
+
+        // the message carries the whole error chain, including the io error
+        let missing = dir.path().join("missing").join("staged");
+        let resp = create_sector_file(
+            RegisteredSealProof::StackedDrg2KiBV1_1,
+            as_bytes(&missing).into(),
+            false,
+        );
+        let msg = str::from_utf8(&resp.error_msg).unwrap();
+        assert!(msg.starts_with("could not create sector file"), "{}", msg);
+        assert!(msg.contains("No such file or directory"), "{}", msg);
+        destroy_create_sector_file_response(resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_piece() -> Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1;
+
+        let mut rng = thread_rng();
+        let buf: Vec<u8> = (0..508).map(|_| rng.gen()).collect();
+
+        let mut src_a = tempfile::tempfile()?;
+        src_a.write_all(&buf[0..127])?;
+        src_a.seek(SeekFrom::Start(0))?;
+
+        let mut src_b = tempfile::tempfile()?;
+        src_b.write_all(&buf[0..508])?;
+        src_b.seek(SeekFrom::Start(0))?;
+
+        let mut staged = tempfile::tempfile()?;
+
+        let resp = unsafe {
+            add_piece(
+                registered_proof,
+                src_a.as_raw_fd(),
+                127,
+                staged.as_raw_fd(),
+                (&[][..]).into(),
+            )
+        };
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("add_piece failed: {:?}", msg);
+        }
+        assert_eq!(resp.offset_padded, 0);
+        assert_eq!(resp.padded_piece_size, 128);
+        destroy_add_piece_response(resp);
+
+        // the offset is derived from the existing pieces, not the fd position
+        staged.seek(SeekFrom::Start(0))?;
+
+        let existing = vec![127u64];
+        let resp = unsafe {
+            add_piece(
+                registered_proof,
+                src_b.as_raw_fd(),
+                508,
+                staged.as_raw_fd(),
+                existing[..].into(),
+            )
+        };
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("add_piece failed: {:?}", msg);
+        }
+        assert_eq!(resp.offset_padded, 512);
+        assert_eq!(resp.padded_piece_size, 512);
+
+        let expected = seal::generate_piece_commitment(
+            registered_proof.into(),
+            &buf[0..508],
+            UnpaddedBytesAmount(508),
+        )?;
+        assert_eq!(resp.comm_p, expected.commitment);
+        destroy_add_piece_response(resp);
+
+        assert_eq!(staged.metadata()?.len(), 1024);
+
+        // a piece that needs no left alignment after the existing ones
+        let mut src_c = tempfile::tempfile()?;
+        src_c.write_all(&buf[0..254])?;
+        src_c.seek(SeekFrom::Start(0))?;
+
+        let existing = vec![127u64, 508];
+        let resp = unsafe {
+            add_piece(
+                registered_proof,
+                src_c.as_raw_fd(),
+                254,
+                staged.as_raw_fd(),
+                existing[..].into(),
+            )
+        };
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("add_piece failed: {:?}", msg);
+        }
+        assert_eq!(resp.offset_padded, 1024);
+        assert_eq!(resp.padded_piece_size, 256);
+        destroy_add_piece_response(resp);
+
+        Ok(())
+    }
+
     #[test]
     fn test_proof_types() {
         let seal_types = vec![
@@ -1495,6 +2729,454 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_verify_seal_with_err_buf() {
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1;
+        let bogus_proof = [0u8; 4];
+
+        let mut err_buf = [0xffu8; 256];
+        let mut valid = true;
+        let status = verify_seal_with_err_buf(
+            registered_proof,
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            1,
+            bogus_proof[..].into(),
+            (&mut err_buf[..]).into(),
+            &mut valid,
+        );
+        assert_ne!(status, FCPResponseStatus::NoError);
+        assert!(valid, "value must not be written on error");
+        let len = err_buf
+            .iter()
+            .position(|&b| b == 0)
+            .expect("NUL terminated");
+        assert!(len > 0);
+
+        // messages are truncated to the buffer
+        let mut small_buf = [0xffu8; 4];
+        let status = verify_seal_with_err_buf(
+            registered_proof,
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            &[0u8; 32],
+            1,
+            bogus_proof[..].into(),
+            (&mut small_buf[..]).into(),
+            &mut valid,
+        );
+        assert_ne!(status, FCPResponseStatus::NoError);
+        assert_eq!(&small_buf[..3], &err_buf[..3]);
+        assert_eq!(small_buf[3], 0);
+    }
+
+    #[test]
+    fn test_get_piece_alignment() {
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1;
+
+        let resp = get_piece_alignment(registered_proof, vec![127u64][..].into(), 508);
+        assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+        assert_eq!(resp.left_alignment_unpadded, 381);
+        assert_eq!(resp.right_alignment_unpadded, 0);
+        assert_eq!(resp.padded_piece_size, 512);
+        destroy_get_piece_alignment_response(resp);
+
+        let resp = get_piece_alignment(registered_proof, vec![508u64][..].into(), 127);
+        assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+        assert_eq!(resp.left_alignment_unpadded, 0);
+        destroy_get_piece_alignment_response(resp);
+
+        // not a power of two multiple of 127
+        let resp = get_piece_alignment(registered_proof, Vec::<u64>::new()[..].into(), 200);
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_get_piece_alignment_response(resp);
+
+        let resp = get_piece_alignment(registered_proof, Vec::<u64>::new()[..].into(), 0);
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_get_piece_alignment_response(resp);
+
+        // far larger than any sector, rejected before the alignment is computed
+        let resp = get_piece_alignment(registered_proof, Vec::<u64>::new()[..].into(), u64::MAX);
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_get_piece_alignment_response(resp);
+
+        // exactly fills a 2KiB sector
+        let resp = get_piece_alignment(registered_proof, vec![1016u64][..].into(), 1016);
+        assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_get_piece_alignment_response(resp);
+
+        // does not fit after the existing piece
+        let resp = get_piece_alignment(registered_proof, vec![127u64][..].into(), 2032);
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_get_piece_alignment_response(resp);
+    }
+
+    #[test]
+    fn test_tee_to_reader() -> Result<()> {
+        let mut rng = thread_rng();
+        let data: Vec<u8> = (0..(3 << 20) + 17).map(|_| rng.gen()).collect();
+
+        let mut written = Vec::new();
+        let (mut tee, mut copy) = helpers::tee_to_reader(&mut written);
+        let reader = std::thread::spawn(move || {
+            let mut copied = Vec::new();
+            copy.read_to_end(&mut copied).map(|_| copied)
+        });
+
+        tee.write_all(&data)?;
+        drop(tee);
+
+        assert_eq!(reader.join().unwrap()?, data);
+        assert_eq!(written, data);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_piece_alignment_matches_add_piece() -> Result<()> {
+        let registered_proof = RegisteredSealProof::StackedDrg8MiBV1;
+        let piece_sizes = [127u64, 1016, 254, 508, 4064, 127];
+
+        let mut staged = tempfile::tempfile()?;
+        let mut existing: Vec<u64> = Vec::new();
+        for &size in &piece_sizes {
+            let existing_sizes: Vec<UnpaddedBytesAmount> =
+                existing.iter().copied().map(UnpaddedBytesAmount).collect();
+            let (_, written) = seal::add_piece(
+                registered_proof.into(),
+                &vec![7u8; size as usize][..],
+                &mut staged,
+                UnpaddedBytesAmount(size),
+                &existing_sizes,
+            )?;
+
+            let before = sum_piece_bytes_with_alignment(&existing);
+            let (left, right) = helpers::get_piece_alignment(before, size);
+            assert_eq!(u64::from(written), left + size + right, "piece of {}", size);
+
+            existing.push(size);
+            assert_eq!(
+                sum_piece_bytes_with_alignment(&existing),
+                before + u64::from(written)
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_piece_commitments() -> Result<()> {
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1;
+
+        let mut rng = thread_rng();
+        let mut files = Vec::new();
+        for size in [127usize, 1016] {
+            let buf: Vec<u8> = (0..size).map(|_| rng.gen()).collect();
+            let mut file = tempfile::NamedTempFile::new()?;
+            file.write_all(&buf)?;
+            files.push((file, size as u64));
+        }
+
+        let paths: Vec<c_slice::Box<u8>> = files
+            .iter()
+            .map(|(file, _)| as_bytes(file.path()).to_vec().into_boxed_slice().into())
+            .collect();
+        let sizes: Vec<u64> = files.iter().map(|(_, size)| *size).collect();
+
+        let resp = generate_piece_commitments(registered_proof, paths[..].into(), sizes[..].into());
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("generate_piece_commitments failed: {:?}", msg);
+        }
+        assert_eq!(resp.len(), 2);
+
+        for ((file, size), commitment) in files.iter().zip(resp.iter()) {
+            let expected = seal::generate_piece_commitment(
+                registered_proof.into(),
+                fs::File::open(file.path())?,
+                UnpaddedBytesAmount(*size),
+            )?;
+            assert_eq!(commitment.comm_p, expected.commitment);
+        }
+        destroy_generate_piece_commitments_response(resp);
+
+        let resp =
+            generate_piece_commitments(registered_proof, paths[..].into(), sizes[..1].into());
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+        destroy_generate_piece_commitments_response(resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_piece_commitment_from_bytes() {
+        let registered_proof = RegisteredSealProof::StackedDrg2KiBV1;
+
+        // the commitments of the all zero pieces of 128 and 256 padded bytes
+        let zero_128 = [
+            0x37, 0x31, 0xbb, 0x99, 0xac, 0x68, 0x9f, 0x66, 0xee, 0xf5, 0x97, 0x3e, 0x4a, 0x94,
+            0xda, 0x18, 0x8f, 0x4d, 0xdc, 0xae, 0x58, 0x07, 0x24, 0xfc, 0x6f, 0x3f, 0xd6, 0x0d,
+            0xfd, 0x48, 0x83, 0x33,
+        ];
+        let zero_256 = [
+            0x64, 0x2a, 0x60, 0x7e, 0xf8, 0x86, 0xb0, 0x04, 0xbf, 0x2c, 0x19, 0x78, 0x46, 0x3a,
+            0xe1, 0xd4, 0x69, 0x3a, 0xc0, 0xf4, 0x10, 0xeb, 0x2d, 0x1b, 0x7a, 0x47, 0xfe, 0x20,
+            0x5e, 0x5e, 0x75, 0x0f,
+        ];
+
+        for (size, comm_p, aligned) in [
+            (0, zero_128, 127),
+            (100, zero_128, 127),
+            (127, zero_128, 127),
+            (128, zero_256, 254),
+            (254, zero_256, 254),
+        ] {
+            let buf = vec![0u8; size];
+            let resp = generate_piece_commitment_from_bytes(registered_proof, buf[..].into());
+            assert_eq!(
+                resp.status_code,
+                FCPResponseStatus::NoError,
+                "size {}",
+                size
+            );
+            assert_eq!(resp.comm_p, comm_p, "size {}", size);
+            assert_eq!(resp.num_bytes_aligned, aligned, "size {}", size);
+            destroy_generate_piece_commitment_response(resp);
+        }
+
+        let buf = vec![0u8; 2033];
+        let resp = generate_piece_commitment_from_bytes(registered_proof, buf[..].into());
+        assert_eq!(resp.status_code, FCPResponseStatus::CallerError);
+        destroy_generate_piece_commitment_response(resp);
+    }
+
+    #[test]
+    fn test_take_error_msg() {
+        let mut resp = cid_to_commitment(CommitmentKind::Sealed, b"not a cid"[..].into());
+        assert_ne!(resp.status_code, FCPResponseStatus::NoError);
+
+        let msg = std::mem::replace(&mut resp.error_msg, alloc_boxed_slice(0));
+        destroy_cid_to_commitment_response(resp);
+
+        assert!(!msg.is_empty());
+        destroy_string(msg);
+    }
+
+    #[test]
+    fn test_write_padded_and_unpadded() -> Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut rng = thread_rng();
+        let buf: Vec<u8> = (0..254).map(|_| rng.gen()).collect();
+
+        let mut src = tempfile::tempfile()?;
+        src.write_all(&buf)?;
+        src.seek(SeekFrom::Start(0))?;
+        let padded = tempfile::tempfile()?;
+        let mut unpadded = tempfile::tempfile()?;
+
+        let resp = unsafe { write_padded(src.as_raw_fd(), padded.as_raw_fd()) };
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("write_padded failed: {:?}", msg);
+        }
+        assert_eq!(resp.value, 256, "two 127 byte chunks pad to 256 bytes");
+        destroy_write_fr32_response(resp);
+
+        let resp = unsafe { write_unpadded(padded.as_raw_fd(), unpadded.as_raw_fd(), 10, 200) };
+        if resp.status_code != FCPResponseStatus::NoError {
+            let msg = str::from_utf8(&resp.error_msg).unwrap();
+            panic!("write_unpadded failed: {:?}", msg);
+        }
+        assert_eq!(resp.value, 200);
+        destroy_write_fr32_response(resp);
+
+        let mut out = Vec::new();
+        unpadded.seek(SeekFrom::Start(0))?;
+        unpadded.read_to_end(&mut out)?;
+        assert_eq!(&out[..], &buf[10..210]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_fd_is_caller_error() -> Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let dst = tempfile::tempfile()?;
+
+        let resp = unsafe { write_padded(-1, dst.as_raw_fd()) };
+        assert_eq!(resp.status_code, FCPResponseStatus::CallerError);
+        let msg = str::from_utf8(&resp.error_msg).unwrap();
+        assert!(msg.contains("src_fd"), "unexpected error: {}", msg);
+        destroy_write_fr32_response(resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_self_test() {
+        let resp = run_self_test();
+        assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+        assert_eq!(resp.len(), 6);
+
+        for stage in resp.iter() {
+            assert_eq!(
+                stage.status_code,
+                FCPResponseStatus::NoError,
+                "stage {} failed: {}",
+                str::from_utf8(&stage.name).unwrap(),
+                str::from_utf8(&stage.error_msg).unwrap()
+            );
+        }
+
+        destroy_run_self_test_response(resp);
+    }
+
+    #[test]
+    fn test_duplicate_replicas_are_rejected() {
+        let replica = |sector_id| PublicReplicaInfo {
+            registered_proof: RegisteredPoStProof::StackedDrgWindow2KiBV1,
+            comm_r: [1u8; 32],
+            sector_id,
+        };
+
+        let unique = vec![replica(1), replica(2)];
+        let map = to_public_replica_info_map(unique[..].into()).unwrap();
+        assert_eq!(map.len(), 2);
+
+        let duplicated = vec![replica(1), replica(2), replica(1)];
+        let err = to_public_replica_info_map(duplicated[..].into()).unwrap_err();
+        assert!(err.to_string().contains("sector 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_derive_challenge_seed() {
+        let randomness = [0xffu8; 32];
+        let seed = derive_challenge_seed(&randomness);
+
+        assert_eq!(&seed[..31], &randomness[..31]);
+        assert_eq!(seed[31], 0x3f);
+        assert!(bytes_into_fr(&seed).is_ok(), "seed must be a valid scalar");
+        assert!(bytes_into_fr(&randomness).is_err());
+
+        // already safe seeds are left untouched
+        assert_eq!(derive_challenge_seed(&seed), seed);
+    }
+
+    #[test]
+    fn test_padded_unpadded_conversion() {
+        assert_eq!(padded_to_unpadded(128), 127);
+        assert_eq!(unpadded_to_padded(127), 128);
+        assert_eq!(padded_to_unpadded(2048), 2032);
+        assert_eq!(unpadded_to_padded(2032), 2048);
+    }
+
+    #[test]
+    fn test_get_supported_sector_sizes() {
+        let resp = get_supported_sector_sizes();
+        assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+
+        let sizes: Vec<u64> = resp.iter().map(|s| s.sector_size).collect();
+        assert_eq!(sizes, vec![2 << 10, 8 << 20, 512 << 20, 32 << 30, 64 << 30]);
+
+        for size in resp.iter() {
+            let seal = api::RegisteredSealProof::from(size.registered_seal_proof);
+            let window = api::RegisteredPoStProof::from(size.registered_window_post_proof);
+            assert_eq!(u64::from(window.sector_size()), size.sector_size);
+            assert_eq!(u64::from(seal.sector_size()), size.sector_size);
+        }
+
+        destroy_get_supported_sector_sizes_response(resp);
+    }
+
+    #[test]
+    fn test_seal_partitions() {
+        assert_eq!(
+            get_seal_partitions(RegisteredSealProof::StackedDrg2KiBV1_1),
+            1
+        );
+        assert_eq!(
+            get_seal_partitions(RegisteredSealProof::StackedDrg32GiBV1_1),
+            10
+        );
+        assert_eq!(get_single_partition_proof_len(), 192);
+        assert_eq!(
+            get_seal_proof_len(RegisteredSealProof::StackedDrg32GiBV1_1),
+            1920
+        );
+        assert_eq!(
+            get_post_proof_len(RegisteredPoStProof::StackedDrgWinning32GiBV1),
+            192
+        );
+    }
+
+    #[test]
+    fn test_post_challenge_counts() {
+        let winning = RegisteredPoStProof::StackedDrgWinning2KiBV1;
+        assert_eq!(get_post_challenge_count(winning), 66);
+        assert_eq!(get_post_sectors_per_partition(winning), 1);
+        assert_eq!(get_post_challenges_per_partition(winning), 66);
+
+        let window = RegisteredPoStProof::StackedDrgWindow32GiBV1;
+        assert_eq!(get_post_challenge_count(window), 10);
+        assert_eq!(get_post_sectors_per_partition(window), 2349);
+        assert_eq!(get_post_challenges_per_partition(window), 23490);
+    }
+
+    #[test]
+    fn test_verify_seal_rejects_bad_proof_len() {
+        let proof = vec![0u8; 100];
+        let resp = verify_seal(
+            RegisteredSealProof::StackedDrg2KiBV1_1,
+            &[1u8; 32],
+            &[2u8; 32],
+            &[3u8; 32],
+            &[4u8; 32],
+            &[5u8; 32],
+            42,
+            proof[..].into(),
+        );
+        assert_eq!(resp.status_code, FCPResponseStatus::CallerError);
+        let msg = str::from_utf8(&resp.error_msg).unwrap();
+        assert!(
+            msg.contains("expected 192 bytes (1 partition(s)), got 100 bytes"),
+            "unexpected error: {}",
+            msg
+        );
+        destroy_verify_seal_response(resp);
+    }
+
+    #[test]
+    fn test_commitment_cid_roundtrip() {
+        let commitment = [7u8; 32];
+
+        for kind in [CommitmentKind::Sealed, CommitmentKind::Unsealed] {
+            let resp = commitment_to_cid(kind, &commitment);
+            assert_eq!(resp.status_code, FCPResponseStatus::NoError);
+
+            let resp2 = cid_to_commitment(kind, resp.as_ref());
+            assert_eq!(resp2.status_code, FCPResponseStatus::NoError);
+            assert_eq!(&**resp2, &commitment);
+
+            destroy_cid_to_commitment_response(resp2);
+            destroy_commitment_to_cid_response(resp);
+        }
+
+        // a sealed cid is not a valid unsealed cid
+        let resp = commitment_to_cid(CommitmentKind::Sealed, &commitment);
+        let resp2 = cid_to_commitment(CommitmentKind::Unsealed, resp.as_ref());
+        assert_ne!(resp2.status_code, FCPResponseStatus::NoError);
+
+        destroy_cid_to_commitment_response(resp2);
+        destroy_commitment_to_cid_response(resp);
+    }
+
     #[test]
     #[allow(clippy::cognitive_complexity)]
     fn test_sealing_v1() -> Result<()> {
@@ -1680,6 +3362,25 @@ pub mod tests {
 
             assert!(**resp_d, "proof was not valid");
 
+            let resp_d_pieces = verify_seal_with_pieces(
+                registered_proof_seal,
+                &resp_b2.comm_r,
+                pieces[..].into(),
+                &prover_id,
+                &ticket,
+                &seed,
+                sector_id,
+                resp_c2.as_ref(),
+            );
+
+            if resp_d_pieces.status_code != FCPResponseStatus::NoError {
+                let msg = str::from_utf8(&resp_d_pieces.error_msg).unwrap();
+                panic!("verify_seal_with_pieces failed: {:?}", msg);
+            }
+
+            assert!(**resp_d_pieces, "proof was not valid for pieces");
+            destroy_verify_seal_response(resp_d_pieces);
+
             let resp_c22 = seal_commit_phase2(resp_c1.as_ref(), sector_id, &prover_id);
 
             if resp_c22.status_code != FCPResponseStatus::NoError {
@@ -2055,6 +3756,84 @@ pub mod tests {
                 "original bytes don't match unsealed bytes"
             );
 
+            // unseal again, checking the whole sector against comm_d
+            {
+                use std::os::unix::io::AsRawFd;
+
+                let sealed = std::fs::File::open(&sealed_path)?;
+                let mut verified = tempfile::tempfile()?;
+
+                let resp = unsafe {
+                    unseal_range_verified(
+                        registered_proof_seal,
+                        cache_dir_path_ref.into(),
+                        sealed.as_raw_fd(),
+                        verified.as_raw_fd(),
+                        sector_id,
+                        &prover_id,
+                        &ticket,
+                        &resp_b2.comm_d,
+                        0,
+                        2032,
+                        &resp_b2.comm_d,
+                    )
+                };
+                if resp.status_code != FCPResponseStatus::NoError {
+                    let msg = str::from_utf8(&resp.error_msg).unwrap();
+                    panic!("unseal_range_verified failed: {:?}", msg);
+                }
+                destroy_unseal_range_response(resp);
+
+                let mut buf_v = Vec::with_capacity(2032);
+                verified.seek(SeekFrom::Start(0))?;
+                verified.read_to_end(&mut buf_v)?;
+                assert_eq!(buf_v, buf_b, "verified unseal output differs");
+
+                let resp = unsafe {
+                    unseal_range_verified(
+                        registered_proof_seal,
+                        cache_dir_path_ref.into(),
+                        sealed.as_raw_fd(),
+                        verified.as_raw_fd(),
+                        sector_id,
+                        &prover_id,
+                        &ticket,
+                        &resp_b2.comm_d,
+                        0,
+                        2032,
+                        &[0u8; 32],
+                    )
+                };
+                assert_ne!(
+                    resp.status_code,
+                    FCPResponseStatus::NoError,
+                    "a commitment mismatch must be reported"
+                );
+                destroy_unseal_range_response(resp);
+
+                let resp = unsafe {
+                    unseal_range_verified(
+                        registered_proof_seal,
+                        cache_dir_path_ref.into(),
+                        sealed.as_raw_fd(),
+                        verified.as_raw_fd(),
+                        sector_id,
+                        &prover_id,
+                        &ticket,
+                        &resp_b2.comm_d,
+                        2000,
+                        u64::MAX,
+                        &resp_b2.comm_d,
+                    )
+                };
+                assert_eq!(
+                    resp.status_code,
+                    FCPResponseStatus::CallerError,
+                    "a range outside the sector must be rejected"
+                );
+                destroy_unseal_range_response(resp);
+            }
+
             // generate a PoSt
 
             let sectors = vec![sector_id];
@@ -2119,6 +3898,43 @@ pub mod tests {
                 panic!("verify_winning_post rejected the provided proof as invalid");
             }
 
+            // randomness that isn't a canonical scalar is usable once normalized
+            {
+                let seed = derive_challenge_seed(&[0xffu8; 32]);
+                let resp_h_seed =
+                    generate_winning_post(&seed, private_replicas[..].into(), &prover_id);
+                if resp_h_seed.status_code != FCPResponseStatus::NoError {
+                    let msg = str::from_utf8(&resp_h_seed.error_msg).unwrap();
+                    panic!("generate_winning_post failed: {:?}", msg);
+                }
+
+                let resp_i_seed = verify_winning_post(
+                    &seed,
+                    public_replicas[..].into(),
+                    resp_h_seed.as_ref(),
+                    &prover_id,
+                );
+                if resp_i_seed.status_code != FCPResponseStatus::NoError {
+                    let msg = str::from_utf8(&resp_i_seed.error_msg).unwrap();
+                    panic!("verify_winning_post failed: {:?}", msg);
+                }
+                assert!(**resp_i_seed, "the normalized seed was rejected");
+
+                // verification normalizes the raw randomness itself
+                let resp_i_raw = verify_winning_post(
+                    &[0xffu8; 32],
+                    public_replicas[..].into(),
+                    resp_h_seed.as_ref(),
+                    &prover_id,
+                );
+                assert_eq!(resp_i_raw.status_code, FCPResponseStatus::NoError);
+                assert!(**resp_i_raw, "the raw seed was rejected");
+
+                destroy_verify_winning_post_response(resp_i_raw);
+                destroy_verify_winning_post_response(resp_i_seed);
+                destroy_generate_winning_post_response(resp_h_seed);
+            }
+
             //////////////////////////////////////////////
             // Winning PoSt using distributed API
             //
@@ -2239,6 +4055,41 @@ pub mod tests {
                 panic!("verify_window_post rejected the provided proof as invalid");
             }
 
+            let resp_check = check_sectors(&randomness, private_replicas[..].into(), &prover_id);
+            if resp_check.status_code != FCPResponseStatus::NoError {
+                let msg = str::from_utf8(&resp_check.error_msg).unwrap();
+                panic!("check_sectors failed: {:?}", msg);
+            }
+            assert_eq!(resp_check.len(), 1);
+            assert!(
+                resp_check[0].ok,
+                "sector check failed: {}",
+                str::from_utf8(&resp_check[0].error_msg).unwrap()
+            );
+            destroy_check_sectors_response(resp_check);
+
+            // raw, non-canonical randomness is normalized like PoSt seeds
+            let resp_check = check_sectors(&[0xffu8; 32], private_replicas[..].into(), &prover_id);
+            assert_eq!(resp_check.status_code, FCPResponseStatus::NoError);
+            assert!(
+                resp_check[0].ok,
+                "sector check failed: {}",
+                str::from_utf8(&resp_check[0].error_msg).unwrap()
+            );
+            destroy_check_sectors_response(resp_check);
+
+            let missing_replicas = vec![PrivateReplicaInfo {
+                registered_proof: registered_proof_window_post,
+                cache_dir_path: cache_dir_path_ref.to_vec().into_boxed_slice().into(),
+                comm_r: resp_b2.comm_r,
+                replica_path: b"/nonexistent/sealed".to_vec().into_boxed_slice().into(),
+                sector_id,
+            }];
+            let resp_check = check_sectors(&randomness, missing_replicas[..].into(), &prover_id);
+            assert_eq!(resp_check.status_code, FCPResponseStatus::NoError);
+            assert!(!resp_check[0].ok, "missing sealed sector passed the check");
+            destroy_check_sectors_response(resp_check);
+
             //////////////////////////////////////////////
             // Window PoSt using distributed API
             //
@@ -1,11 +1,399 @@
 use std::collections::btree_map::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::sync::mpsc;
 
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
 use filecoin_proofs_api::{self as api, SectorId};
 use safer_ffi::prelude::*;
 
 use super::types::{PrivateReplicaInfo, PublicReplicaInfo, RegisteredPoStProof};
-use crate::util::types::as_path_buf;
+use crate::util::types::{as_path_buf, CallerError};
+
+/// The length of a single partition proof: a compressed Groth16 proof over
+/// BLS12-381 (two G1 points and one G2 point).
+pub const SINGLE_PARTITION_PROOF_LEN: usize = 192;
+
+/// Describes how many partition proofs `proof_len` bytes hold, for errors.
+fn describe_partitions(proof_len: usize) -> String {
+    if proof_len % SINGLE_PARTITION_PROOF_LEN == 0 {
+        format!("{} partition(s)", proof_len / SINGLE_PARTITION_PROOF_LEN)
+    } else {
+        "not a whole number of partitions".to_string()
+    }
+}
+
+/// Checks that a seal proof holds exactly one partition proof per partition of
+/// `registered_proof`, so that truncated or concatenated proofs are reported as
+/// caller errors with the expected and actual lengths.
+pub fn ensure_seal_proof_len(
+    registered_proof: api::RegisteredSealProof,
+    proof_len: usize,
+) -> Result<()> {
+    let partitions = usize::from(registered_proof.as_v1_config().partitions);
+    let expected = seal_proof_len(registered_proof);
+
+    if proof_len != expected {
+        return Err(CallerError(format!(
+            "invalid seal proof length for {:?}: expected {} bytes ({} partition(s)), got {} bytes ({})",
+            registered_proof,
+            expected,
+            partitions,
+            proof_len,
+            describe_partitions(proof_len),
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Returns the length in bytes of a seal proof of the provided type.
+pub fn seal_proof_len(registered_proof: api::RegisteredSealProof) -> usize {
+    usize::from(registered_proof.as_v1_config().partitions) * SINGLE_PARTITION_PROOF_LEN
+}
+
+/// Checks that a PoSt proof is made of whole partition proofs: exactly one for
+/// winning PoSt, at least one for window PoSt, where the partition count
+/// depends on the number of sectors proven.
+pub fn ensure_post_proof_len(
+    registered_proof: RegisteredPoStProof,
+    proof_len: usize,
+) -> Result<()> {
+    use RegisteredPoStProof::*;
+
+    let is_winning = matches!(
+        registered_proof,
+        StackedDrgWinning2KiBV1
+            | StackedDrgWinning8MiBV1
+            | StackedDrgWinning512MiBV1
+            | StackedDrgWinning32GiBV1
+            | StackedDrgWinning64GiBV1
+    );
+    let valid = if is_winning {
+        proof_len == SINGLE_PARTITION_PROOF_LEN
+    } else {
+        proof_len > 0 && proof_len % SINGLE_PARTITION_PROOF_LEN == 0
+    };
+
+    if !valid {
+        let expected = if is_winning {
+            format!("{} bytes (1 partition)", SINGLE_PARTITION_PROOF_LEN)
+        } else {
+            format!(
+                "a non-zero multiple of {} bytes",
+                SINGLE_PARTITION_PROOF_LEN
+            )
+        };
+
+        return Err(CallerError(format!(
+            "invalid PoSt proof length for {:?}: expected {}, got {} bytes ({})",
+            registered_proof,
+            expected,
+            proof_len,
+            describe_partitions(proof_len),
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Clears the two most significant bits of the (little-endian) `randomness`,
+/// so that it is a canonical BLS12-381 scalar as the proofs library requires
+/// of challenge seeds.
+pub fn into_safe_challenge_seed(randomness: &[u8; 32]) -> [u8; 32] {
+    let mut seed = *randomness;
+    seed[31] &= 0b0011_1111;
+    seed
+}
+
+/// Checks that `fd` refers to an open file descriptor before it is wrapped in
+/// a `File`, reporting a caller error naming the argument otherwise.
+pub fn ensure_valid_fd(fd: libc::c_int, name: &str) -> Result<()> {
+    // F_GETFD has no side effects and fails with EBADF for closed descriptors
+    if fd < 0 || unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(CallerError(format!("{} is not an open file descriptor: {}", name, fd)).into());
+    }
+
+    Ok(())
+}
+
+/// Verifies a vanilla proof returned by `generate_single_vanilla_proof` for a
+/// single sector, the way the window PoSt circuit would: its challenges must be
+/// those derived from `randomness`, `prover_id` and `sector_id`, every merkle
+/// path must lead to its comm_r_last, and comm_r_last and comm_c must hash to
+/// `comm_r`. No SNARK is generated.
+pub fn verify_single_vanilla_proof(
+    registered_proof: api::RegisteredPoStProof,
+    randomness: &[u8; 32],
+    prover_id: &[u8; 32],
+    sector_id: SectorId,
+    comm_r: &[u8; 32],
+    vanilla_proof: &[u8],
+) -> Result<bool> {
+    use filecoin_proofs_v1::constants::{
+        SECTOR_SIZE_2_KIB, SECTOR_SIZE_32_GIB, SECTOR_SIZE_512_MIB, SECTOR_SIZE_64_GIB,
+        SECTOR_SIZE_8_MIB,
+    };
+    use filecoin_proofs_v1::types::{
+        SectorShape2KiB, SectorShape32GiB, SectorShape512MiB, SectorShape64GiB, SectorShape8MiB,
+    };
+
+    let args = (
+        registered_proof,
+        randomness,
+        prover_id,
+        sector_id,
+        comm_r,
+        vanilla_proof,
+    );
+    match u64::from(registered_proof.sector_size()) {
+        SECTOR_SIZE_2_KIB => verify_single_vanilla_proof_inner::<SectorShape2KiB>(args),
+        SECTOR_SIZE_8_MIB => verify_single_vanilla_proof_inner::<SectorShape8MiB>(args),
+        SECTOR_SIZE_512_MIB => verify_single_vanilla_proof_inner::<SectorShape512MiB>(args),
+        SECTOR_SIZE_32_GIB => verify_single_vanilla_proof_inner::<SectorShape32GiB>(args),
+        SECTOR_SIZE_64_GIB => verify_single_vanilla_proof_inner::<SectorShape64GiB>(args),
+        size => anyhow::bail!("unsupported sector size: {}", size),
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn verify_single_vanilla_proof_inner<
+    Tree: 'static + storage_proofs_core::merkle::MerkleTreeTrait,
+>(
+    (registered_proof, randomness, prover_id, sector_id, comm_r, vanilla_proof): (
+        api::RegisteredPoStProof,
+        &[u8; 32],
+        &[u8; 32],
+        SectorId,
+        &[u8; 32],
+        &[u8],
+    ),
+) -> Result<bool> {
+    use filecoin_proofs_v1::{as_safe_commitment, FallbackPoStSectorProof};
+    use storage_proofs_core::proof::ProofScheme;
+    use storage_proofs_post::fallback::{FallbackPoSt, PublicInputs, PublicParams, PublicSector};
+
+    let proof: FallbackPoStSectorProof<Tree> =
+        bincode::deserialize(vanilla_proof).context("could not decode vanilla proof")?;
+    ensure!(
+        proof.sector_id == sector_id,
+        "vanilla proof is for sector {:?}, expected {:?}",
+        proof.sector_id,
+        sector_id
+    );
+
+    let config = registered_proof.as_v1_config();
+    let pub_params = PublicParams {
+        sector_size: u64::from(config.sector_size),
+        challenge_count: config.challenge_count,
+        sector_count: 1,
+        api_version: config.api_version,
+    };
+    let pub_inputs = PublicInputs {
+        randomness: as_safe_commitment(randomness, "randomness")?,
+        prover_id: as_safe_commitment(prover_id, "prover_id")?,
+        sectors: vec![PublicSector {
+            id: sector_id,
+            comm_r: as_safe_commitment(comm_r, "comm_r")?,
+        }],
+        k: None,
+    };
+
+    FallbackPoSt::<Tree>::verify(&pub_params, &pub_inputs, &proof.vanilla_proof)
+}
+
+/// The smallest piece, in unpadded bytes, that can be added to a sector.
+const MINIMUM_PIECE_SIZE: u64 = 127;
+
+/// Returns the `(left, right)` padding, in unpadded bytes, that `add_piece`
+/// inserts when writing a piece of `piece_bytes` after `written_bytes`. Every
+/// piece is placed at an offset that is a multiple of its size rounded up to
+/// the next power of two multiple of 127, and padded on the right up to that
+/// size, so that its merkle root is unaffected by neighbouring pieces.
+pub fn get_piece_alignment(written_bytes: u64, piece_bytes: u64) -> (u64, u64) {
+    let mut piece_bytes_needed = MINIMUM_PIECE_SIZE;
+    while piece_bytes_needed < piece_bytes {
+        // callers validate sizes against the sector size, this only stops
+        // unchecked input from looping forever in release builds
+        piece_bytes_needed = piece_bytes_needed
+            .checked_mul(2)
+            .expect("piece size larger than any sector");
+    }
+
+    let encroaching = written_bytes % piece_bytes_needed;
+    let left_bytes = if encroaching > 0 {
+        piece_bytes_needed - encroaching
+    } else {
+        0
+    };
+    let right_bytes = piece_bytes_needed - piece_bytes;
+
+    (left_bytes, right_bytes)
+}
+
+/// Returns the number of unpadded bytes occupied by `pieces` once they are
+/// written in order, including their alignment.
+pub fn sum_piece_bytes_with_alignment(pieces: &[u64]) -> u64 {
+    pieces.iter().fold(0, |acc, &piece_bytes| {
+        let (left_bytes, right_bytes) = get_piece_alignment(acc, piece_bytes);
+        acc + left_bytes + piece_bytes + right_bytes
+    })
+}
+
+/// Reserves the first `len` bytes of `file` on disk, so that writing them
+/// later can't fail for lack of space.
+#[cfg(target_os = "linux")]
+pub fn preallocate(file: &File, len: u64) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let len = libc::off_t::try_from(len)?;
+    // posix_fallocate returns the error instead of setting errno
+    let res = unsafe { libc::posix_fallocate(file.as_raw_fd(), 0, len) };
+    ensure!(res == 0, io::Error::from_raw_os_error(res));
+
+    Ok(())
+}
+
+/// Reserves the first `len` bytes of `file` on disk, so that writing them
+/// later can't fail for lack of space.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn preallocate(file: &File, len: u64) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut store = libc::fstore_t {
+        fst_flags: libc::F_ALLOCATECONTIG | libc::F_ALLOCATEALL,
+        fst_posmode: libc::F_PEOFPOSMODE,
+        fst_offset: 0,
+        fst_length: libc::off_t::try_from(len)?,
+        fst_bytesalloc: 0,
+    };
+    let mut res = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &store) };
+    if res == -1 {
+        // retry without asking for contiguous blocks
+        store.fst_flags = libc::F_ALLOCATEALL;
+        res = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_PREALLOCATE, &store) };
+    }
+    ensure!(res != -1, io::Error::last_os_error());
+
+    Ok(())
+}
+
+/// Preallocation is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "ios")))]
+pub fn preallocate(_file: &File, _len: u64) -> Result<()> {
+    anyhow::bail!("preallocating files is not supported on this platform")
+}
+
+/// Creates a file in `dir` and removes its name right away, so that it is
+/// deleted once closed, whatever happens to the caller.
+pub fn create_unlinked_file_in(dir: &Path) -> Result<File> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+
+    let path = dir.join(format!(
+        ".filcrypto-tmp-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .with_context(|| format!("could not create temporary file {:?}", path))?;
+    std::fs::remove_file(&path)?;
+
+    Ok(file)
+}
+
+/// The largest chunk a [`TeeWriter`] passes on at once, so that the bytes in
+/// flight stay bounded regardless of the size of the writes.
+const TEE_CHUNK_SIZE: usize = 1 << 20;
+
+/// Returns a writer that writes to `inner` and passes a copy of every byte
+/// written to the returned reader, so that the same bytes can be consumed on
+/// another thread while they are written. The reader sees EOF once the writer
+/// is dropped; writes fail once the reader is dropped.
+pub fn tee_to_reader<W: Write>(inner: W) -> (TeeWriter<W>, TeeReader) {
+    let (sender, receiver) = mpsc::sync_channel(16);
+    let writer = TeeWriter {
+        inner,
+        copy: sender,
+    };
+    let reader = TeeReader {
+        chunks: receiver,
+        chunk: Vec::new(),
+        pos: 0,
+        ended: false,
+    };
+
+    (writer, reader)
+}
+
+/// The writer half of [`tee_to_reader`].
+pub struct TeeWriter<W> {
+    inner: W,
+    copy: mpsc::SyncSender<Vec<u8>>,
+}
+
+impl<W: Write> Write for TeeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buf = &buf[..buf.len().min(TEE_CHUNK_SIZE)];
+        let n = self.inner.write(buf)?;
+        self.copy
+            .send(buf[..n].to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the tee reader was dropped"))?;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The reader half of [`tee_to_reader`].
+pub struct TeeReader {
+    chunks: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+    ended: bool,
+}
+
+impl TeeReader {
+    /// Whether the writer was dropped and everything it wrote was read.
+    pub fn reached_end(&self) -> bool {
+        self.ended
+    }
+}
+
+impl Read for TeeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.chunks.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                // the writer was dropped
+                Err(_) => {
+                    self.ended = true;
+                    return Ok(0);
+                }
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
 
 #[derive(Debug, Clone)]
 struct PublicReplicaInfoTmp {
@@ -14,9 +402,24 @@ struct PublicReplicaInfoTmp {
     pub sector_id: u64,
 }
 
+/// Collects `(sector_id, info)` pairs into a map, failing if a sector id
+/// occurs more than once rather than silently keeping only the last entry.
+fn into_unique_map<T>(entries: Vec<(SectorId, T)>) -> Result<BTreeMap<SectorId, T>> {
+    let mut map = BTreeMap::new();
+    for (sector_id, info) in entries {
+        ensure!(
+            map.insert(sector_id, info).is_none(),
+            "duplicate replica info for sector {}",
+            u64::from(sector_id)
+        );
+    }
+
+    Ok(map)
+}
+
 pub fn to_public_replica_info_map(
     replicas: c_slice::Ref<PublicReplicaInfo>,
-) -> BTreeMap<SectorId, api::PublicReplicaInfo> {
+) -> Result<BTreeMap<SectorId, api::PublicReplicaInfo>> {
     use rayon::prelude::*;
 
     let replicas = replicas
@@ -28,7 +431,7 @@ pub fn to_public_replica_info_map(
         })
         .collect::<Vec<_>>();
 
-    replicas
+    let entries = replicas
         .into_par_iter()
         .map(|info| {
             let PublicReplicaInfoTmp {
@@ -42,7 +445,9 @@ pub fn to_public_replica_info_map(
                 api::PublicReplicaInfo::new(registered_proof.into(), comm_r),
             )
         })
-        .collect()
+        .collect();
+
+    into_unique_map(entries)
 }
 
 #[derive(Debug, Clone)]
@@ -75,7 +480,7 @@ pub fn to_private_replica_info_map(
         })
         .collect::<Result<_>>()?;
 
-    let map = replicas
+    let entries = replicas
         .into_par_iter()
         .map(|info| {
             let PrivateReplicaInfoTmp {
@@ -98,5 +503,5 @@ pub fn to_private_replica_info_map(
         })
         .collect();
 
-    Ok(map)
+    into_unique_map(entries)
 }
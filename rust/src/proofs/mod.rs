@@ -1,4 +1,5 @@
 mod helpers;
+mod verification_cache;
 
 pub mod api;
 pub mod types;
@@ -3,7 +3,7 @@ use std::io::SeekFrom;
 use filecoin_proofs_api as api;
 use safer_ffi::prelude::*;
 
-use crate::util::types::Result;
+use crate::util::types::{FCPResponseStatus, Result};
 
 /// FileDescriptorRef does not drop its file descriptor when it is dropped. Its
 /// owner must manage the lifecycle of the file descriptor.
@@ -15,6 +15,10 @@ impl FileDescriptorRef {
         use std::os::unix::io::FromRawFd;
         FileDescriptorRef(std::mem::ManuallyDrop::new(std::fs::File::from_raw_fd(raw)))
     }
+
+    pub fn as_file(&self) -> &std::fs::File {
+        &self.0
+    }
 }
 
 impl std::io::Read for FileDescriptorRef {
@@ -226,6 +230,8 @@ impl From<&PublicPieceInfo> for api::PieceInfo {
 
 pub type VanillaProof = c_slice::Box<u8>;
 
+/// The aggregated proof bytes are owned by the response and freed by
+/// `destroy_aggregate_proof`.
 pub type AggregateProof = Result<VanillaProof>;
 
 #[derive(Clone, Debug)]
@@ -333,8 +339,24 @@ pub struct GenerateFallbackSectorChallenges {
 
 pub type GenerateSingleVanillaProofResponse = Result<VanillaProof>;
 
+/// The outcome of `check_sectors` for one sector. `error_msg` is empty for
+/// sectors that passed.
+#[derive_ReprC]
+#[repr(C)]
+pub struct SectorCheck {
+    pub sector_id: u64,
+    pub ok: bool,
+    pub error_msg: c_slice::Box<u8>,
+}
+
+pub type CheckSectorsResponse = Result<c_slice::Box<SectorCheck>>;
+
+/// The proofs, and the bytes they point to, are owned by the response and freed
+/// by `destroy_generate_winning_post_response`.
 pub type GenerateWinningPoStResponse = Result<c_slice::Box<PoStProof>>;
 
+/// The proofs and faulty sectors are owned by the response and freed by
+/// `destroy_generate_window_post_response`.
 pub type GenerateWindowPoStResponse = Result<GenerateWindowPoSt>;
 
 #[derive_ReprC]
@@ -372,6 +394,8 @@ pub struct WriteWithAlignment {
 
 pub type WriteWithoutAlignmentResponse = Result<WriteWithoutAlignment>;
 
+pub type WriteFr32Response = Result<u64>;
+
 #[derive_ReprC]
 #[repr(C)]
 #[derive(Default)]
@@ -406,6 +430,8 @@ impl Default for SealPreCommitPhase2 {
 
 pub type SealCommitPhase1Response = Result<c_slice::Box<u8>>;
 
+/// The proof bytes are owned by the response and freed by
+/// `destroy_seal_commit_phase2_response`.
 pub type SealCommitPhase2Response = Result<c_slice::Box<u8>>;
 
 #[derive_ReprC]
@@ -433,6 +459,31 @@ pub type FinalizeTicketResponse = Result<[u8; 32]>;
 
 pub type GeneratePieceCommitmentResponse = Result<GeneratePieceCommitment>;
 
+#[derive_ReprC]
+#[repr(C)]
+#[derive(Default)]
+pub struct PieceAlignment {
+    pub left_alignment_unpadded: u64,
+    pub right_alignment_unpadded: u64,
+    pub padded_piece_size: u64,
+}
+
+pub type GetPieceAlignmentResponse = Result<PieceAlignment>;
+
+#[derive_ReprC]
+#[repr(C)]
+#[derive(Default)]
+pub struct AddPiece {
+    pub comm_p: [u8; 32],
+    /// Offset of the piece in the staged sector file, in padded bytes.
+    pub offset_padded: u64,
+    pub padded_piece_size: u64,
+}
+
+pub type AddPieceResponse = Result<AddPiece>;
+
+pub type GeneratePieceCommitmentsResponse = Result<c_slice::Box<GeneratePieceCommitment>>;
+
 #[derive_ReprC]
 #[repr(C)]
 #[derive(Default)]
@@ -445,10 +496,52 @@ pub struct GeneratePieceCommitment {
 
 pub type GenerateDataCommitmentResponse = Result<[u8; 32]>;
 
+/// The kind of commitment being converted to or from a CID.
+#[derive_ReprC]
+#[repr(i32)]
+#[derive(Debug, Clone, Copy)]
+pub enum CommitmentKind {
+    /// comm_r, encoded as `fil-commitment-sealed` with a poseidon multihash.
+    Sealed,
+    /// comm_d or comm_p, encoded as `fil-commitment-unsealed` with a sha2-256-trunc254-padded multihash.
+    Unsealed,
+}
+
+pub type CommitmentToCidResponse = Result<c_slice::Box<u8>>;
+
+pub type CidToCommitmentResponse = Result<[u8; 32]>;
+
 pub type StringResponse = Result<c_slice::Box<u8>>;
 
+/// The outcome of a single stage of `run_self_test`.
+#[derive_ReprC]
+#[repr(C)]
+pub struct SelfTestStage {
+    pub name: c_slice::Box<u8>,
+    pub status_code: FCPResponseStatus,
+    pub error_msg: c_slice::Box<u8>,
+    pub duration_ms: u64,
+}
+
+pub type RunSelfTestResponse = Result<c_slice::Box<SelfTestStage>>;
+
+/// A sector size supported by this build, with the proof types used for it.
+#[derive_ReprC]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SupportedSectorSize {
+    pub sector_size: u64,
+    pub registered_seal_proof: RegisteredSealProof,
+    pub registered_winning_post_proof: RegisteredPoStProof,
+    pub registered_window_post_proof: RegisteredPoStProof,
+}
+
+pub type GetSupportedSectorSizesResponse = Result<c_slice::Box<SupportedSectorSize>>;
+
 pub type ClearCacheResponse = Result<()>;
 
+pub type CreateSectorFileResponse = Result<()>;
+
 pub type EmptySectorUpdateEncodeIntoResponse = Result<EmptySectorUpdateEncodeInto>;
 
 #[derive_ReprC]
@@ -464,6 +557,8 @@ pub type EmptySectorUpdateDecodeFromResponse = Result<()>;
 
 pub type EmptySectorUpdateRemoveEncodedDataResponse = Result<()>;
 
+/// The proof bytes are owned by the response and freed by
+/// `destroy_empty_sector_update_generate_proof_response`.
 pub type EmptySectorUpdateProofResponse = Result<c_slice::Box<u8>>;
 
 pub type PartitionProofResponse = Result<c_slice::Box<ApiPartitionProof>>;
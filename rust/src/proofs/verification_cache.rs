@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+/// A least recently used set of inputs that verified successfully. Only
+/// successful verifications are remembered, so a hit can never turn a valid
+/// proof into an invalid one, and entries are keyed on the full inputs rather
+/// than a digest of them, so a hit can never accept a proof that was not
+/// verified before.
+#[derive(Default)]
+struct Cache {
+    /// The maximum number of entries, 0 when caching is disabled.
+    capacity: usize,
+    /// The last use of every entry.
+    entries: HashMap<Vec<u8>, u64>,
+    /// The entries by last use, oldest first.
+    by_use: BTreeMap<u64, Vec<u8>>,
+    tick: u64,
+}
+
+lazy_static! {
+    static ref CACHE: Mutex<Cache> = Mutex::new(Cache::default());
+}
+
+impl Cache {
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = *self.by_use.keys().next().expect("entries out of sync");
+            let key = self.by_use.remove(&oldest).expect("entries out of sync");
+            self.entries.remove(&key);
+        }
+    }
+
+    fn touch(&mut self, key: &[u8]) -> bool {
+        self.tick += 1;
+        match self.entries.get_mut(key) {
+            Some(last_use) => {
+                let key = self.by_use.remove(last_use).expect("entries out of sync");
+                *last_use = self.tick;
+                self.by_use.insert(self.tick, key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert(&mut self, key: Vec<u8>) {
+        if self.capacity == 0 || self.touch(&key) {
+            return;
+        }
+        self.entries.insert(key.clone(), self.tick);
+        self.by_use.insert(self.tick, key);
+        self.evict();
+    }
+}
+
+/// Sets the maximum number of remembered verifications, dropping the least
+/// recently used ones over the new size. 0 disables the cache and clears it.
+pub fn set_size(size: u64) {
+    CACHE.lock().unwrap().set_capacity(size as usize);
+}
+
+/// Builds a cache key from the given parts. Parts are length prefixed, so
+/// different inputs can't produce the same key.
+pub fn key(parts: &[&[u8]]) -> Vec<u8> {
+    let mut key = Vec::with_capacity(parts.iter().map(|part| 8 + part.len()).sum());
+    for part in parts {
+        key.extend_from_slice(&(part.len() as u64).to_le_bytes());
+        key.extend_from_slice(part);
+    }
+    key
+}
+
+/// Returns true if the inputs returned by `key` verified successfully before,
+/// otherwise runs `verify` and remembers the inputs if it returns true. `key`
+/// is only called when the cache is enabled.
+pub fn verify_cached(
+    key: impl FnOnce() -> Vec<u8>,
+    verify: impl FnOnce() -> anyhow::Result<bool>,
+) -> anyhow::Result<bool> {
+    if CACHE.lock().unwrap().capacity == 0 {
+        return verify();
+    }
+
+    let key = key();
+    if CACHE.lock().unwrap().touch(&key) {
+        return Ok(true);
+    }
+
+    let valid = verify()?;
+    if valid {
+        CACHE.lock().unwrap().insert(key);
+    }
+    Ok(valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = Cache::default();
+        cache.insert(key(&[b"a"]));
+        assert!(
+            !cache.touch(&key(&[b"a"])),
+            "disabled cache stored an entry"
+        );
+
+        cache.set_capacity(2);
+        cache.insert(key(&[b"a"]));
+        cache.insert(key(&[b"b"]));
+        assert!(cache.touch(&key(&[b"a"])));
+
+        // "b" is now the least recently used entry
+        cache.insert(key(&[b"c"]));
+        assert!(cache.touch(&key(&[b"a"])));
+        assert!(!cache.touch(&key(&[b"b"])));
+        assert!(cache.touch(&key(&[b"c"])));
+
+        cache.set_capacity(0);
+        assert!(cache.entries.is_empty());
+        assert!(cache.by_use.is_empty());
+    }
+
+    #[test]
+    fn test_key_is_unambiguous() {
+        assert_ne!(key(&[b"ab", b"c"]), key(&[b"a", b"bc"]));
+        assert_ne!(key(&[b"", b"a"]), key(&[b"a", b""]));
+    }
+}
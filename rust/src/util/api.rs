@@ -1,23 +1,39 @@
 use std::fs::File;
 use std::os::unix::io::FromRawFd;
-use std::sync::Once;
+use std::path::PathBuf;
+use std::sync::{Mutex, Once};
 
 use anyhow::anyhow;
+use lazy_static::lazy_static;
+use log::LevelFilter;
 use safer_ffi::prelude::*;
 
 use super::types::{
-    catch_panic_response, catch_panic_response_no_log, GpuDeviceResponse, InitLogFdResponse,
+    as_path_buf, catch_panic_response, catch_panic_response_no_log, outstanding_responses,
+    CallerError, GpuDeviceResponse, InitConfig, InitFilecoinFfiResponse, InitLogFdResponse,
+    InitLogFileResponse, InitLogResponse, OutstandingResponseCount,
+    OutstandingResponseCountsResponse, ShutdownLogResponse,
 };
 
 /// Protects the init off the logger.
 static LOG_INIT: Once = Once::new();
 
-/// Ensures the logger is initialized.
-pub fn init_log() {
+lazy_static! {
+    /// Whether `init_filecoin_ffi` succeeded, held while it applies the configuration.
+    static ref CONFIG_APPLIED: Mutex<bool> = Mutex::new(false);
+    /// The log level that was active before `shutdown_log` silenced the logger,
+    /// `None` while the logger is running.
+    static ref LOG_LEVEL_BEFORE_SHUTDOWN: Mutex<Option<LevelFilter>> = Mutex::new(None);
+}
+
+/// Ensures the logger is initialized, logging to stderr if none of the
+/// `init_log*` functions was called before.
+pub fn ensure_log_initialized() {
     LOG_INIT.call_once(|| {
         fil_logger::init();
     });
 }
+
 /// Initialize the logger with a file to log into
 ///
 /// Returns `None` if there is already an active logger
@@ -32,6 +48,46 @@ pub fn init_log_with_file(file: File) -> Option<()> {
     }
 }
 
+/// Initializes the logger to log to stderr, or resumes it after `shutdown_log`.
+///
+/// Like `init_log_fd`, this must be called before any other FFI function, otherwise the logger
+/// is already initialized implicitly and an error is returned.
+#[ffi_export]
+pub fn init_log() -> repr_c::Box<InitLogResponse> {
+    catch_panic_response_no_log(|| {
+        if let Some(level) = LOG_LEVEL_BEFORE_SHUTDOWN.lock().unwrap().take() {
+            log::set_max_level(level);
+            return Ok(());
+        }
+
+        if LOG_INIT.is_completed() {
+            return Err(anyhow!("There is already an active logger. `init_log()` needs to be called before any other FFI function is called."));
+        }
+        ensure_log_initialized();
+
+        Ok(())
+    })
+}
+
+/// Flushes and silences the logger.
+///
+/// The process wide logger can only be installed once, so it is not dropped: `init_log` resumes
+/// logging to the original destination. Logging to a different file descriptor after a shutdown
+/// is not supported.
+#[ffi_export]
+pub fn shutdown_log() -> repr_c::Box<ShutdownLogResponse> {
+    catch_panic_response_no_log(|| {
+        let mut level_before_shutdown = LOG_LEVEL_BEFORE_SHUTDOWN.lock().unwrap();
+        if LOG_INIT.is_completed() && level_before_shutdown.is_none() {
+            log::logger().flush();
+            *level_before_shutdown = Some(log::max_level());
+            log::set_max_level(LevelFilter::Off);
+        }
+
+        Ok(())
+    })
+}
+
 /// Returns an array of strings containing the device names that can be used.
 #[ffi_export]
 pub fn get_gpu_devices() -> repr_c::Box<GpuDeviceResponse> {
@@ -46,6 +102,131 @@ pub fn get_gpu_devices() -> repr_c::Box<GpuDeviceResponse> {
     })
 }
 
+/// Applies the process wide configuration.
+///
+/// The proofs library reads its settings and the logger its filter once, on first use, so this
+/// must be called once, before any other FFI function. Later calls return an error once a call
+/// has succeeded. Every field is validated before anything is applied, so a call that fails
+/// changes nothing and can be retried with a fixed config.
+///
+/// The settings are passed to the proofs library and the logger through environment variables.
+/// Setting them is not synchronized with `getenv` calls made by C code on other threads, so this
+/// must be called before any thread that may read the environment is started, e.g. at the start
+/// of the host program's `main`.
+#[ffi_export]
+pub fn init_filecoin_ffi(config: &InitConfig) -> repr_c::Box<InitFilecoinFfiResponse> {
+    catch_panic_response_no_log(|| {
+        let mut applied = CONFIG_APPLIED.lock().unwrap();
+        if *applied || LOG_INIT.is_completed() {
+            return Err(anyhow!("`init_filecoin_ffi()` must be called once, before any other FFI function is called."));
+        }
+
+        apply_init_config(config)?;
+        *applied = true;
+
+        Ok(())
+    })
+}
+
+fn apply_init_config(config: &InitConfig) -> anyhow::Result<()> {
+    use std::ffi::OsString;
+
+    // `set_var` panics on values containing a NUL byte
+    let env_value = |bytes: &[u8], field: &str| -> anyhow::Result<OsString> {
+        if bytes.contains(&0) {
+            return Err(CallerError(format!("invalid {}: contains a NUL byte", field)).into());
+        }
+        let path =
+            as_path_buf(bytes).map_err(|err| CallerError(format!("invalid {}: {}", field, err)))?;
+        Ok(path.into())
+    };
+
+    let mut env: Vec<(&str, OsString)> = Vec::new();
+    if !config.log_level.is_empty() {
+        let level = std::str::from_utf8(&config.log_level)
+            .map_err(|err| CallerError(format!("invalid log_level: {}", err)))?;
+        env.push(("RUST_LOG", env_value(level.as_bytes(), "log_level")?));
+    }
+    if !config.param_cache_path.is_empty() {
+        let path = env_value(&config.param_cache_path, "param_cache_path")?;
+        env.push(("FIL_PROOFS_PARAMETER_CACHE", path));
+    }
+    if !config.parent_cache_path.is_empty() {
+        let path = env_value(&config.parent_cache_path, "parent_cache_path")?;
+        env.push(("FIL_PROOFS_PARENT_CACHE", path));
+    }
+    if config.disable_gpu {
+        env.push(("BELLMAN_NO_GPU", "1".into()));
+        env.push(("FIL_PROOFS_USE_GPU_COLUMN_BUILDER", "0".into()));
+        env.push(("FIL_PROOFS_USE_GPU_TREE_BUILDER", "0".into()));
+    }
+
+    // the only step that can fail once the config is valid, so it goes before any other
+    if config.num_threads > 0 {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(config.num_threads as usize)
+            .build_global()?;
+    }
+    for (name, value) in env {
+        std::env::set_var(name, value);
+    }
+    super::limiter::configure(
+        [
+            config.max_concurrent_pc1,
+            config.max_concurrent_pc2,
+            config.max_concurrent_c2,
+            config.max_concurrent_window_post,
+        ],
+        config.queue_when_busy,
+    );
+
+    Ok(())
+}
+
+/// Returns the names of the GPU backends this library was compiled with,
+/// `"cuda"` and/or `"opencl"`. The list is empty for CPU only builds.
+#[ffi_export]
+pub fn get_gpu_backends() -> repr_c::Box<GpuDeviceResponse> {
+    catch_panic_response("get_gpu_backends", || {
+        let mut backends = Vec::new();
+        if cfg!(feature = "cuda") {
+            backends.push(b"cuda".to_vec().into_boxed_slice().into());
+        }
+        if cfg!(feature = "opencl") {
+            backends.push(b"opencl".to_vec().into_boxed_slice().into());
+        }
+
+        Ok(backends.into_boxed_slice().into())
+    })
+}
+
+/// Enables or disables counting of the responses returned by this library and
+/// destroyed by the caller, for finding missing `destroy_*` calls in bindings.
+/// Enabling resets the counts. Tracking adds a global lock to every call and
+/// is meant for debugging only.
+#[ffi_export]
+pub fn set_response_tracking(enabled: bool) {
+    super::types::set_response_tracking(enabled);
+}
+
+/// Returns, for every response type with a non-zero count, the number of
+/// responses returned since `set_response_tracking(true)` that have not been
+/// destroyed yet. The returned response itself is not included.
+#[ffi_export]
+pub fn get_outstanding_response_counts() -> repr_c::Box<OutstandingResponseCountsResponse> {
+    catch_panic_response_no_log(|| {
+        let counts: Vec<OutstandingResponseCount> = outstanding_responses()
+            .into_iter()
+            .map(|(name, outstanding)| OutstandingResponseCount {
+                response_type: name.as_bytes().to_vec().into_boxed_slice().into(),
+                outstanding,
+            })
+            .collect();
+
+        Ok(counts.into_boxed_slice().into())
+    })
+}
+
 /// Initializes the logger with a file descriptor where logs will be logged into.
 ///
 /// This is usually a pipe that was opened on the receiving side of the logs. The logger is
@@ -65,11 +246,154 @@ pub fn init_log_fd(log_fd: libc::c_int) -> repr_c::Box<InitLogFdResponse> {
     })
 }
 
+/// Initializes the logger to append to the file at `path`, creating it if needed.
+///
+/// If `max_size_bytes` is non-zero the file is rotated once it reaches that size: `path` is
+/// renamed to `path.1`, `path.1` to `path.2` and so on, keeping at most `max_files` rotated
+/// files, which must then be between 1 and `MAX_LOG_FILES`. The size is checked when the logger
+/// is initialized and every 10 seconds afterwards, so a file can briefly exceed the limit.
+///
+/// Like `init_log_fd`, this must be called before any other FFI function.
+#[ffi_export]
+pub fn init_log_file(
+    path: c_slice::Ref<u8>,
+    max_size_bytes: u64,
+    max_files: u64,
+) -> repr_c::Box<InitLogFileResponse> {
+    catch_panic_response_no_log(|| {
+        use std::os::unix::io::AsRawFd;
+
+        if max_size_bytes > 0 && !(1..=MAX_LOG_FILES).contains(&max_files) {
+            return Err(CallerError(format!(
+                "invalid max_files: {}, expected between 1 and {}",
+                max_files, MAX_LOG_FILES
+            ))
+            .into());
+        }
+
+        if LOG_INIT.is_completed() {
+            return Err(anyhow!("There is already an active logger. `init_log_file()` needs to be called before any other FFI function is called."));
+        }
+
+        let path = as_path_buf(&path)?;
+        let rotation = LogRotation {
+            path,
+            max_size_bytes,
+            max_files,
+        };
+
+        rotation.rotate_if_needed()?;
+        let file = rotation.open()?;
+        let log_fd = file.as_raw_fd();
+
+        if init_log_with_file(file).is_none() {
+            return Err(anyhow!("There is already an active logger. `init_log_file()` needs to be called before any other FFI function is called."));
+        }
+
+        if max_size_bytes > 0 {
+            std::thread::Builder::new()
+                .name("log-rotation".into())
+                .spawn(move || loop {
+                    std::thread::sleep(std::time::Duration::from_secs(10));
+                    if let Err(err) = rotation.rotate_fd_if_needed(log_fd) {
+                        log::warn!("failed to rotate log file: {}", err);
+                    }
+                })?;
+        }
+
+        Ok(())
+    })
+}
+
+/// The most rotated log files `init_log_file` keeps, which bounds the renames
+/// done on every rotation.
+pub const MAX_LOG_FILES: u64 = 100;
+
+/// Size based rotation of the log file set up by `init_log_file`.
+struct LogRotation {
+    path: PathBuf,
+    max_size_bytes: u64,
+    max_files: u64,
+}
+
+impl LogRotation {
+    fn open(&self) -> std::io::Result<File> {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+    }
+
+    fn rotated_path(&self, n: u64) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+
+    /// Renames the log file out of the way if it is at or above the maximum
+    /// size. Returns whether it was rotated.
+    fn rotate_if_needed(&self) -> std::io::Result<bool> {
+        if self.max_size_bytes == 0 {
+            return Ok(false);
+        }
+        match std::fs::metadata(&self.path) {
+            Ok(meta) if meta.len() >= self.max_size_bytes => {}
+            Ok(_) => return Ok(false),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(err) => return Err(err),
+        }
+
+        // the oldest file is overwritten by the rename below
+        for n in (1..self.max_files.min(MAX_LOG_FILES)).rev() {
+            let from = self.rotated_path(n);
+            if from.exists() {
+                std::fs::rename(&from, self.rotated_path(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))?;
+
+        Ok(true)
+    }
+
+    /// Rotates the log file and points `log_fd`, which the logger writes to,
+    /// at a fresh file.
+    fn rotate_fd_if_needed(&self, log_fd: libc::c_int) -> std::io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        if self.rotate_if_needed()? {
+            let file = self.open()?;
+            // atomically replaces the logger's descriptor, the old file is
+            // closed once no write is in flight
+            if unsafe { libc::dup2(file.as_raw_fd(), log_fd) } == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use crate::util::api::get_gpu_devices;
-    use crate::util::types::destroy_gpu_device_response;
+    use crate::util::api::{get_gpu_backends, get_gpu_devices};
+    use crate::util::types::{
+        destroy_gpu_device_response, new_response, write_response_into, Result,
+    };
+
+    #[test]
+    fn test_write_response_into_keeps_utf8() {
+        let mut err_buf = [0xffu8; 4];
+        let mut value = false;
+        let response = new_response(Result::<bool>::from_anyhow(Err(anyhow::anyhow!("hé!"))));
+        write_response_into(response, (&mut err_buf[..]).into(), &mut value);
+        assert_eq!(&err_buf, b"h\xc3\xa9\0");
+
+        let mut err_buf = [0xffu8; 3];
+        let response = new_response(Result::<bool>::from_anyhow(Err(anyhow::anyhow!("hé!"))));
+        write_response_into(response, (&mut err_buf[..]).into(), &mut value);
+        assert_eq!(&err_buf, b"h\0\xff");
+    }
 
     #[test]
     #[allow(clippy::needless_collect)]
@@ -89,6 +413,154 @@ mod tests {
         destroy_gpu_device_response(resp);
     }
 
+    #[test]
+    fn test_get_gpu_backends() {
+        let resp = get_gpu_backends();
+        assert!(resp.error_msg.is_empty());
+
+        let backends: Vec<&str> = resp
+            .value
+            .iter()
+            .map(|s| std::str::from_utf8(s).unwrap())
+            .collect();
+
+        assert_eq!(backends.contains(&"cuda"), cfg!(feature = "cuda"));
+        assert_eq!(backends.contains(&"opencl"), cfg!(feature = "opencl"));
+
+        destroy_gpu_device_response(resp);
+    }
+
+    #[test]
+    fn test_operation_ids() {
+        let first = get_gpu_backends();
+        let second = get_gpu_backends();
+
+        assert_ne!(first.operation_id, 0);
+        assert_ne!(first.operation_id, second.operation_id);
+        assert_eq!(crate::util::types::current_operation_id(), 0);
+
+        destroy_gpu_device_response(first);
+        destroy_gpu_device_response(second);
+    }
+
+    #[test]
+    fn test_log_rotation() -> std::io::Result<()> {
+        use std::io::Write;
+
+        use super::LogRotation;
+
+        let dir = tempfile::tempdir()?;
+        let rotation = LogRotation {
+            path: dir.path().join("filcrypto.log"),
+            max_size_bytes: 10,
+            max_files: 2,
+        };
+
+        // a missing file is not an error
+        assert!(!rotation.rotate_if_needed()?);
+
+        for i in 0..3 {
+            let mut file = rotation.open()?;
+            write!(file, "generation {}", i)?;
+            assert!(rotation.rotate_if_needed()?);
+        }
+
+        assert!(!rotation.path.exists());
+        assert_eq!(
+            std::fs::read_to_string(rotation.rotated_path(1))?,
+            "generation 2"
+        );
+        assert_eq!(
+            std::fs::read_to_string(rotation.rotated_path(2))?,
+            "generation 1"
+        );
+        assert!(!rotation.rotated_path(3).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_init_log_file_rejects_max_files() {
+        use super::{init_log_file, MAX_LOG_FILES};
+        use crate::util::types::{destroy_init_log_file_response, FCPResponseStatus};
+
+        for max_files in [0, MAX_LOG_FILES + 1, u64::MAX] {
+            let resp = init_log_file(b"filcrypto.log"[..].into(), 10, max_files);
+            assert_eq!(resp.status_code, FCPResponseStatus::CallerError);
+            destroy_init_log_file_response(resp);
+        }
+    }
+
+    #[test]
+    fn test_invalid_init_config_changes_nothing() {
+        use crate::util::types::{CallerError, InitConfig};
+
+        let config = InitConfig {
+            num_threads: 0,
+            disable_gpu: true,
+            param_cache_path: b"/tmp/params\0".to_vec().into_boxed_slice().into(),
+            parent_cache_path: Vec::new().into_boxed_slice().into(),
+            log_level: b"info".to_vec().into_boxed_slice().into(),
+            max_concurrent_pc1: 0,
+            max_concurrent_pc2: 0,
+            max_concurrent_c2: 0,
+            max_concurrent_window_post: 0,
+            queue_when_busy: false,
+        };
+
+        let tree_builder = std::env::var_os("FIL_PROOFS_USE_GPU_TREE_BUILDER");
+        let err = super::apply_init_config(&config).unwrap_err();
+        assert!(err.is::<CallerError>(), "{}", err);
+        assert!(err.to_string().contains("param_cache_path"), "{}", err);
+        assert_eq!(
+            std::env::var_os("FIL_PROOFS_USE_GPU_TREE_BUILDER"),
+            tree_builder
+        );
+    }
+
+    #[test]
+    fn test_outstanding_response_counts() {
+        use std::any::type_name;
+
+        use crate::fvm::machine::drop_fvm_machine;
+        use crate::fvm::types::InnerFvmMachine;
+        use crate::util::api::{get_outstanding_response_counts, set_response_tracking};
+        use crate::util::types::{
+            destroy_outstanding_response_counts_response, new_tracked, track_response,
+        };
+
+        struct Marker;
+        let count_of = |name: &str| {
+            let resp = get_outstanding_response_counts();
+            let count = resp
+                .iter()
+                .find(|c| &c.response_type[..] == name.as_bytes())
+                .map(|c| c.outstanding);
+            destroy_outstanding_response_counts_response(resp);
+            count
+        };
+        let marker = type_name::<Marker>();
+
+        set_response_tracking(true);
+        track_response::<Marker>(1);
+        track_response::<Marker>(1);
+        assert_eq!(count_of(marker), Some(2));
+
+        track_response::<Marker>(-1);
+        track_response::<Marker>(-1);
+        assert_eq!(count_of(marker), None);
+
+        // boxes other than responses are counted when created, not only when
+        // destroyed
+        let machine_type = type_name::<InnerFvmMachine>();
+        let machine = new_tracked(InnerFvmMachine { machine: None });
+        assert_eq!(count_of(machine_type), Some(1));
+        drop_fvm_machine(machine);
+        assert_eq!(count_of(machine_type), None);
+
+        set_response_tracking(false);
+    }
+
     #[test]
     #[ignore]
     #[cfg(target_os = "linux")]
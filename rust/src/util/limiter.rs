@@ -0,0 +1,141 @@
+use std::sync::{Condvar, Mutex};
+
+use lazy_static::lazy_static;
+
+use super::types::Busy;
+
+/// The expensive operations whose concurrency can be limited through
+/// `InitConfig`. Winning PoSt is never limited, so that it can't be delayed
+/// behind other jobs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Job {
+    PreCommit1,
+    PreCommit2,
+    Commit2,
+    WindowPoSt,
+}
+
+const JOBS: usize = 4;
+
+#[derive(Default)]
+struct State {
+    /// The maximum number of concurrent jobs per kind, 0 for no limit.
+    max: [usize; JOBS],
+    running: [usize; JOBS],
+    /// Whether calls over the limit wait for a slot instead of failing.
+    queue: bool,
+}
+
+#[derive(Default)]
+struct Limiter {
+    state: Mutex<State>,
+    released: Condvar,
+}
+
+lazy_static! {
+    static ref LIMITER: Limiter = Limiter::default();
+}
+
+impl Limiter {
+    fn configure(&self, max: [u64; JOBS], queue: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.max = max.map(|max| max as usize);
+        state.queue = queue;
+        self.released.notify_all();
+    }
+
+    fn acquire(&self, job: Job) -> anyhow::Result<JobGuard<'_>> {
+        let i = job as usize;
+        let mut state = self.state.lock().unwrap();
+
+        while state.max[i] > 0 && state.running[i] >= state.max[i] {
+            if !state.queue {
+                return Err(Busy(format!(
+                    "{:?}: {} of {} jobs already running",
+                    job, state.running[i], state.max[i]
+                ))
+                .into());
+            }
+            state = self.released.wait(state).unwrap();
+        }
+        state.running[i] += 1;
+
+        Ok(JobGuard { limiter: self, job })
+    }
+}
+
+/// Sets the limits applied by `acquire`, indexed like [`Job`]. Jobs that are
+/// already running count against the new limits.
+pub fn configure(max: [u64; JOBS], queue: bool) {
+    LIMITER.configure(max, queue)
+}
+
+/// Reserves a slot for `job`, waiting for one or failing with [`Busy`] if the
+/// limit is reached, depending on the configuration. The slot is released
+/// when the returned guard is dropped.
+pub fn acquire(job: Job) -> anyhow::Result<JobGuard<'static>> {
+    LIMITER.acquire(job)
+}
+
+/// A running job counted against its limit, see [`acquire`].
+pub struct JobGuard<'a> {
+    limiter: &'a Limiter,
+    job: Job,
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state.lock().unwrap();
+        state.running[self.job as usize] -= 1;
+        self.limiter.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_limit_without_queue() {
+        let limiter = Limiter::default();
+        limiter.configure([0, 0, 1, 0], false);
+
+        let guard = limiter.acquire(Job::Commit2).unwrap();
+        let err = limiter.acquire(Job::Commit2).err().unwrap();
+        assert!(err.is::<Busy>(), "{}", err);
+
+        // other jobs are unaffected
+        drop(limiter.acquire(Job::PreCommit2).unwrap());
+
+        drop(guard);
+        drop(limiter.acquire(Job::Commit2).unwrap());
+    }
+
+    #[test]
+    fn test_limit_with_queue() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let limiter = Arc::new(Limiter::default());
+        limiter.configure([1, 0, 0, 0], true);
+        let acquired = Arc::new(AtomicBool::new(false));
+
+        let guard = limiter.acquire(Job::PreCommit1).unwrap();
+        let waiter = {
+            let limiter = limiter.clone();
+            let acquired = acquired.clone();
+            std::thread::spawn(move || {
+                let _guard = limiter.acquire(Job::PreCommit1).unwrap();
+                acquired.store(true, Ordering::SeqCst);
+            })
+        };
+
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!acquired.load(Ordering::SeqCst), "limit was not enforced");
+
+        drop(guard);
+        waiter.join().unwrap();
+        assert!(acquired.load(Ordering::SeqCst));
+    }
+}
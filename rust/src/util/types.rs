@@ -1,8 +1,14 @@
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
 use std::{fmt::Display, mem::MaybeUninit, ops::Deref, panic, path::PathBuf, str::Utf8Error};
 
+use lazy_static::lazy_static;
 use safer_ffi::prelude::*;
 
-use super::api::init_log;
+use super::api::ensure_log_initialized;
 
 #[derive_ReprC]
 #[repr(i32)]
@@ -13,6 +19,8 @@ pub enum FCPResponseStatus {
     UnclassifiedError = 1,
     CallerError = 2,
     ReceiverError = 3,
+    /// The call was rejected because too many jobs of its kind are running.
+    Busy = 4,
 }
 
 #[cfg(target_os = "linux")]
@@ -41,12 +49,70 @@ pub fn as_bytes(path: &std::path::Path) -> &[u8] {
     path.to_str().unwrap().as_bytes()
 }
 
+/// An error caused by invalid arguments, such as a closed file descriptor or a
+/// buffer of the wrong length. Errors wrapping it are reported with
+/// `FCPResponseStatus::CallerError`, everything else is unclassified.
+#[derive(Debug)]
+pub struct CallerError(pub String);
+
+impl Display for CallerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CallerError {}
+
+/// An error returned instead of starting a job when its concurrency limit is
+/// reached, reported with `FCPResponseStatus::Busy`.
+#[derive(Debug)]
+pub struct Busy(pub String);
+
+impl Display for Busy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Busy {}
+
+/// Returns the message reported for `err`: the error and all of its causes,
+/// outermost first, separated by `: `.
+pub fn error_message(err: &anyhow::Error) -> String {
+    format!("{:#}", err)
+}
+
+/// Returns the status code `err` should be reported with.
+pub fn error_status(err: &anyhow::Error) -> FCPResponseStatus {
+    if err.is::<CallerError>() {
+        FCPResponseStatus::CallerError
+    } else if err.is::<Busy>() {
+        FCPResponseStatus::Busy
+    } else {
+        FCPResponseStatus::UnclassifiedError
+    }
+}
+
+/// The response of every fallible FFI call.
+///
+/// The response, including `error_msg` and any buffers in `value`, is allocated
+/// by Rust and owned by the caller once returned. It must be released with the
+/// `destroy_*` function paired with the call that produced it, never with the
+/// allocator of the host language.
+///
+/// `duration_ns` is the time spent inside the call, excluding the FFI
+/// transition itself. `operation_id` matches the id in the call's start and
+/// end log lines. The fields before `value` are shared by every response, so
+/// changing them breaks the ABI of all of them and needs a version bump and a
+/// changelog entry.
 #[derive_ReprC]
 #[repr(C)]
 #[derive(Clone)]
 pub struct Result<T: Sized> {
     pub status_code: FCPResponseStatus,
     pub error_msg: c_slice::Box<u8>,
+    pub duration_ns: u64,
+    pub operation_id: u64,
     pub value: T,
 }
 
@@ -63,6 +129,8 @@ impl<T: Sized + Default> Default for Result<T> {
         Result {
             status_code: FCPResponseStatus::NoError,
             error_msg: Default::default(),
+            duration_ns: 0,
+            operation_id: 0,
             value: Default::default(),
         }
     }
@@ -76,7 +144,8 @@ where
     fn from(r: std::result::Result<T, E>) -> Self {
         match r {
             Ok(value) => Self::ok(value),
-            Err(e) => Self::err(e.to_string().into_bytes().into_boxed_slice()),
+            // the alternate form includes the causes of anyhow errors
+            Err(e) => Self::err(format!("{:#}", e).into_bytes().into_boxed_slice()),
         }
     }
 }
@@ -89,6 +158,8 @@ where
         Self {
             status_code: FCPResponseStatus::NoError,
             error_msg: Default::default(),
+            duration_ns: 0,
+            operation_id: 0,
             value,
         }
     }
@@ -99,6 +170,8 @@ impl<T: Sized> Result<T> {
         Result {
             status_code: FCPResponseStatus::NoError,
             error_msg: Default::default(),
+            duration_ns: 0,
+            operation_id: 0,
             value,
         }
     }
@@ -111,6 +184,8 @@ impl<T: Sized> Result<T> {
         Result {
             status_code: FCPResponseStatus::UnclassifiedError,
             error_msg: err.into(),
+            duration_ns: 0,
+            operation_id: 0,
             value,
         }
     }
@@ -120,6 +195,8 @@ impl<T: Sized> Result<T> {
         Result {
             status_code: FCPResponseStatus::UnclassifiedError,
             error_msg: err.into(),
+            duration_ns: 0,
+            operation_id: 0,
             value: MaybeUninit::zeroed().assume_init(),
         }
     }
@@ -130,15 +207,32 @@ impl<T: Sized + Default> Result<T> {
         Result {
             status_code: FCPResponseStatus::UnclassifiedError,
             error_msg: err.into(),
+            duration_ns: 0,
+            operation_id: 0,
             value: Default::default(),
         }
     }
+
+    /// Converts `r`, classifying the error with [`error_status`].
+    pub fn from_anyhow(r: anyhow::Result<T>) -> Self {
+        match r {
+            Ok(value) => Self::ok(value),
+            Err(err) => Result {
+                status_code: error_status(&err),
+                error_msg: error_message(&err).into_bytes().into_boxed_slice().into(),
+                duration_ns: 0,
+                operation_id: 0,
+                value: Default::default(),
+            },
+        }
+    }
 }
 
 pub type GpuDeviceResponse = Result<c_slice::Box<c_slice::Box<u8>>>;
 
 #[ffi_export]
 pub fn destroy_gpu_device_response(ptr: repr_c::Box<GpuDeviceResponse>) {
+    track_response::<GpuDeviceResponse>(-1);
     drop(ptr)
 }
 
@@ -146,18 +240,192 @@ pub type InitLogFdResponse = Result<()>;
 
 #[ffi_export]
 pub fn destroy_init_log_fd_response(ptr: repr_c::Box<InitLogFdResponse>) {
+    track_response::<InitLogFdResponse>(-1);
     drop(ptr)
 }
 
+pub type InitLogFileResponse = Result<()>;
+
+#[ffi_export]
+pub fn destroy_init_log_file_response(ptr: repr_c::Box<InitLogFileResponse>) {
+    track_response::<InitLogFileResponse>(-1);
+    drop(ptr)
+}
+
+pub type InitLogResponse = Result<()>;
+
+#[ffi_export]
+pub fn destroy_init_log_response(ptr: repr_c::Box<InitLogResponse>) {
+    track_response::<InitLogResponse>(-1);
+    drop(ptr)
+}
+
+/// Process wide settings applied once by `init_filecoin_ffi`.
+///
+/// Zero values and empty strings keep the default, which is taken from the
+/// environment or the built-in values of the proofs library.
+#[derive_ReprC]
+#[repr(C)]
+pub struct InitConfig {
+    /// Number of threads in the global rayon pool.
+    pub num_threads: u64,
+    /// Disables the GPU for proving and tree building.
+    pub disable_gpu: bool,
+    /// Directory holding the Groth parameters and verifying keys.
+    pub param_cache_path: c_slice::Box<u8>,
+    /// Directory holding the SDR parent cache.
+    pub parent_cache_path: c_slice::Box<u8>,
+    /// Filter in `RUST_LOG` syntax, e.g. `info` or `filcrypto=debug`.
+    pub log_level: c_slice::Box<u8>,
+    /// Maximum number of concurrent `seal_pre_commit_phase1` calls.
+    pub max_concurrent_pc1: u64,
+    /// Maximum number of concurrent `seal_pre_commit_phase2` calls.
+    pub max_concurrent_pc2: u64,
+    /// Maximum number of concurrent `seal_commit_phase2` calls.
+    pub max_concurrent_c2: u64,
+    /// Maximum number of concurrent window PoSt generations. Winning PoSt is
+    /// time critical and never limited.
+    pub max_concurrent_window_post: u64,
+    /// Makes calls over a limit wait for a running job to finish, instead of
+    /// failing with `FCPResponseStatus::Busy`.
+    pub queue_when_busy: bool,
+}
+
+pub type InitFilecoinFfiResponse = Result<()>;
+
+#[ffi_export]
+pub fn destroy_init_filecoin_ffi_response(ptr: repr_c::Box<InitFilecoinFfiResponse>) {
+    track_response::<InitFilecoinFfiResponse>(-1);
+    drop(ptr)
+}
+
+pub type ShutdownLogResponse = Result<()>;
+
+#[ffi_export]
+pub fn destroy_shutdown_log_response(ptr: repr_c::Box<ShutdownLogResponse>) {
+    track_response::<ShutdownLogResponse>(-1);
+    drop(ptr)
+}
+
+/// Whether responses are counted by `track_response`, see
+/// `set_response_tracking`.
+static RESPONSE_TRACKING: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    /// The number of responses returned minus the number destroyed while
+    /// tracking was enabled, by response type.
+    static ref OUTSTANDING_RESPONSES: Mutex<BTreeMap<&'static str, i64>> =
+        Mutex::new(BTreeMap::new());
+}
+
+/// Enables or disables counting of responses. Counts are kept when tracking
+/// is disabled and cleared when it is enabled again.
+pub fn set_response_tracking(enabled: bool) {
+    let mut outstanding = OUTSTANDING_RESPONSES.lock().unwrap();
+    if enabled {
+        outstanding.clear();
+    }
+    RESPONSE_TRACKING.store(enabled, Ordering::SeqCst);
+}
+
+/// Records `delta` responses of type `T` being returned (1) or destroyed (-1)
+/// if tracking is enabled.
+pub fn track_response<T>(delta: i64) {
+    if RESPONSE_TRACKING.load(Ordering::Relaxed) {
+        *OUTSTANDING_RESPONSES
+            .lock()
+            .unwrap()
+            .entry(std::any::type_name::<T>())
+            .or_insert(0) += delta;
+    }
+}
+
+/// Returns the response types with a non-zero outstanding count.
+pub fn outstanding_responses() -> Vec<(&'static str, i64)> {
+    OUTSTANDING_RESPONSES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, &count)| count != 0)
+        .map(|(&name, &count)| (name, count))
+        .collect()
+}
+
+/// Boxes `value` to be returned to the caller, counting it if response
+/// tracking is enabled. Every box released by a `destructor!` function must be
+/// created through this, or its count goes negative.
+pub fn new_tracked<T>(value: T) -> repr_c::Box<T> {
+    track_response::<T>(1);
+    repr_c::Box::new(value)
+}
+
+/// Boxes `response` to be returned to the caller, see `new_tracked`.
+pub fn new_response<T: Sized>(response: Result<T>) -> repr_c::Box<Result<T>> {
+    new_tracked(response)
+}
+
+/// The number of responses of one type that have not been destroyed, see
+/// `get_outstanding_response_counts`.
+#[derive_ReprC]
+#[repr(C)]
+pub struct OutstandingResponseCount {
+    /// The Rust type of the response, e.g.
+    /// `filcrypto::util::types::Result<bool>`. Aliases share a type.
+    pub response_type: c_slice::Box<u8>,
+    /// Responses returned minus responses destroyed; negative if responses
+    /// returned before tracking was enabled were destroyed since.
+    pub outstanding: i64,
+}
+
+pub type OutstandingResponseCountsResponse = Result<c_slice::Box<OutstandingResponseCount>>;
+
+#[ffi_export]
+pub fn destroy_outstanding_response_counts_response(
+    ptr: repr_c::Box<OutstandingResponseCountsResponse>,
+) {
+    track_response::<OutstandingResponseCountsResponse>(-1);
+    drop(ptr)
+}
+
+/// Moves the outcome of `response` into caller owned memory, for callers that
+/// can not release Rust allocated buffers.
+///
+/// On success the value is written to `value`. On failure the error message is
+/// copied into `err_buf`, truncated to the last whole character that fits and
+/// NUL terminated, and `value` is left untouched. The response is destroyed
+/// before returning.
+pub fn write_response_into<T: Copy>(
+    response: repr_c::Box<Result<T>>,
+    mut err_buf: c_slice::Mut<'_, u8>,
+    value: &mut T,
+) -> FCPResponseStatus {
+    track_response::<Result<T>>(-1);
+    if response.status_code == FCPResponseStatus::NoError {
+        *value = response.value;
+    } else if !err_buf.is_empty() {
+        // never cut a character in two, so the truncated message stays UTF-8
+        let msg = String::from_utf8_lossy(&response.error_msg);
+        let max_len = err_buf.len() - 1;
+        let len = msg
+            .char_indices()
+            .map(|(start, c)| start + c.len_utf8())
+            .take_while(|&end| end <= max_len)
+            .last()
+            .unwrap_or(0);
+        err_buf[..len].copy_from_slice(&msg.as_bytes()[..len]);
+        err_buf[len] = 0;
+    }
+
+    response.status_code
+}
+
 /// Catch panics and return an error response
 pub fn catch_panic_response<F, T>(name: &str, callback: F) -> repr_c::Box<Result<T>>
 where
     T: Sized + Default,
     F: FnOnce() -> anyhow::Result<T> + std::panic::UnwindSafe,
 {
-    catch_panic_response_raw(name, || {
-        Result::from(callback().map_err(|err| err.to_string()))
-    })
+    catch_panic_response_raw(name, || Result::from_anyhow(callback()))
 }
 
 pub fn catch_panic_response_no_log<F, T>(callback: F) -> repr_c::Box<Result<T>>
@@ -165,7 +433,7 @@ where
     T: Sized + Default,
     F: FnOnce() -> anyhow::Result<T> + std::panic::UnwindSafe,
 {
-    catch_panic_response_raw_no_log(|| Result::from(callback().map_err(|err| err.to_string())))
+    catch_panic_response_raw_no_log(|| Result::from_anyhow(callback()))
 }
 
 pub fn catch_panic_response_raw_no_log<F, T>(callback: F) -> repr_c::Box<Result<T>>
@@ -173,7 +441,8 @@ where
     T: Sized + Default,
     F: FnOnce() -> Result<T> + std::panic::UnwindSafe,
 {
-    let result = match panic::catch_unwind(callback) {
+    let start = Instant::now();
+    let (operation_id, mut result) = with_operation_id(|| match panic::catch_unwind(callback) {
         Ok(t) => t,
         Err(panic) => {
             let error_msg = match panic.downcast_ref::<&'static str>() {
@@ -183,9 +452,40 @@ where
 
             Result::from(Err(format!("Rust panic: {}", error_msg)))
         }
-    };
+    });
+    result.duration_ns = elapsed_ns(start);
+    result.operation_id = operation_id;
+
+    new_response(result)
+}
+
+/// The source of operation ids, starting at 1 so that 0 means "no operation".
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    /// The id of the FFI call running on this thread.
+    static CURRENT_OPERATION_ID: Cell<u64> = Cell::new(0);
+}
+
+/// Returns the id of the FFI call running on the current thread, or 0 outside
+/// of one. Work moved to other threads, e.g. by rayon, does not inherit it.
+pub fn current_operation_id() -> u64 {
+    CURRENT_OPERATION_ID.with(Cell::get)
+}
+
+/// Runs `f` as a new operation, returning its id with the result. `f` must
+/// not unwind, so that the id of an enclosing call is always restored.
+fn with_operation_id<R>(f: impl FnOnce() -> R) -> (u64, R) {
+    let id = NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed);
+    let outer = CURRENT_OPERATION_ID.with(|current| current.replace(id));
+    let res = f();
+    CURRENT_OPERATION_ID.with(|current| current.set(outer));
 
-    repr_c::Box::new(result)
+    (id, res)
+}
+
+fn elapsed_ns(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX)
 }
 
 pub fn catch_panic_response_raw<F, T>(name: &str, callback: F) -> repr_c::Box<Result<T>>
@@ -194,10 +494,11 @@ where
     F: FnOnce() -> Result<T> + std::panic::UnwindSafe,
 {
     catch_panic_response_raw_no_log(|| {
-        init_log();
-        log::info!("{}: start", name);
+        ensure_log_initialized();
+        let operation_id = current_operation_id();
+        log::info!("{} [{}]: start", name, operation_id);
         let res = callback();
-        log::info!("{}: end", name);
+        log::info!("{} [{}]: end", name, operation_id);
         res
     })
 }
@@ -210,41 +511,59 @@ where
     T: Sized,
     F: FnOnce() -> anyhow::Result<T> + std::panic::UnwindSafe,
 {
-    let result = match panic::catch_unwind(|| {
-        init_log();
-        log::info!("{}: start", name);
-        let res = callback();
-        log::info!("{}: end", name);
-        res
-    }) {
-        Ok(t) => match t {
-            Ok(t) => Result::ok(t),
-            Err(err) => Result::err_no_default(err.to_string().into_bytes().into_boxed_slice()),
-        },
-        Err(panic) => {
-            let error_msg = match panic.downcast_ref::<&'static str>() {
-                Some(message) => message,
-                _ => "no unwind information",
-            };
-
-            Result::err_no_default(
-                format!("Rust panic: {}", error_msg)
-                    .into_bytes()
-                    .into_boxed_slice(),
-            )
+    let start = Instant::now();
+    let (operation_id, mut result) = with_operation_id(|| {
+        match panic::catch_unwind(|| {
+            ensure_log_initialized();
+            let operation_id = current_operation_id();
+            log::info!("{} [{}]: start", name, operation_id);
+            let res = callback();
+            log::info!("{} [{}]: end", name, operation_id);
+            res
+        }) {
+            Ok(t) => match t {
+                Ok(t) => Result::ok(t),
+                Err(err) => {
+                    let status_code = error_status(&err);
+                    let mut res =
+                        Result::err_no_default(error_message(&err).into_bytes().into_boxed_slice());
+                    res.status_code = status_code;
+                    res
+                }
+            },
+            Err(panic) => {
+                let error_msg = match panic.downcast_ref::<&'static str>() {
+                    Some(message) => message,
+                    _ => "no unwind information",
+                };
+
+                Result::err_no_default(
+                    format!("Rust panic: {}", error_msg)
+                        .into_bytes()
+                        .into_boxed_slice(),
+                )
+            }
         }
-    };
+    });
+    result.duration_ns = elapsed_ns(start);
+    result.operation_id = operation_id;
 
-    repr_c::Box::new(result)
+    new_response(result)
 }
 
 /// Generate a destructor for the given type wrapped in a `repr_c::Box`.
+///
+/// All responses share the `status_code`/`error_msg` header of [`Result`] and
+/// the destructors are typed on the full response, so the generated C headers
+/// reject passing a response to the destructor of a different type. Aliases of
+/// the same response type are interchangeable.
 #[macro_export]
 macro_rules! destructor {
     ($name:ident, $type:ty) => {
         /// Destroys the passed in `repr_c::Box<$type>`.
         #[ffi_export]
-        fn $name(ptr: repr_c::Box<$type>) {
+        pub fn $name(ptr: repr_c::Box<$type>) {
+            $crate::util::types::track_response::<$type>(-1);
             drop(ptr);
         }
     };
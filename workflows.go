@@ -280,6 +280,14 @@ func WorkflowRegisteredSealProofFunctions(t TestHelper) {
 		v, err := GetSealVersion(st)
 		t.AssertNoError(err)
 		t.AssertTrue(len(v) > 0)
+
+		vk, err := GetSealVerifyingKeyCid(st)
+		t.AssertNoError(err)
+		t.AssertTrue(len(vk) > 0)
+
+		id, err := GetSealCircuitIdentifier(st)
+		t.AssertNoError(err)
+		t.AssertTrue(len(id) > 0)
 	}
 }
 
@@ -302,6 +310,14 @@ func WorkflowRegisteredPoStProofFunctions(t TestHelper) {
 		v, err := GetPoStVersion(pt)
 		t.AssertNoError(err)
 		t.AssertTrue(len(v) > 0)
+
+		vk, err := GetPoStVerifyingKeyCid(pt)
+		t.AssertNoError(err)
+		t.AssertTrue(len(vk) > 0)
+
+		id, err := GetPoStCircuitIdentifier(pt)
+		t.AssertNoError(err)
+		t.AssertTrue(len(id) > 0)
 	}
 }
 